@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use meadowland::resources::biomes::Biomes;
+use meadowland::systems::map_generation::{generate_map, generate_map_serially, MapParameters};
+use meadowland::systems::rivers::RiverParameters;
+
+fn map_generation(c: &mut Criterion) {
+    let json = std::fs::read_to_string("assets/biomes/details.biomes.json").unwrap();
+    let biomes = serde_json::from_str::<Biomes>(&json).unwrap().biomes;
+
+    let mut group = c.benchmark_group("map_generation");
+    group.sample_size(10);
+    for size in [300, 1000] {
+        let parameters = MapParameters {
+            width: size,
+            height: size,
+            seed: 7,
+            biome_weights: vec![1.0; biomes.len()],
+            rivers: RiverParameters { count: 8, ..Default::default() },
+        };
+        let label = format!("{size}x{size}");
+        group.bench_with_input(BenchmarkId::new("serial", &label), &parameters, |b, parameters| {
+            b.iter(|| generate_map_serially(&biomes, parameters))
+        });
+        group.bench_with_input(BenchmarkId::new("chunked", &label), &parameters, |b, parameters| {
+            b.iter(|| generate_map(&biomes, parameters))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, map_generation);
+criterion_main!(benches);
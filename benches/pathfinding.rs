@@ -0,0 +1,61 @@
+use bevy::math::UVec2;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use meadowland::resources::biomes::Biomes;
+use meadowland::resources::game_map::GameMap;
+use meadowland::resources::path_finder::PathFinder;
+use meadowland::systems::map_generation::{generate_map, MapParameters};
+use meadowland::systems::rivers::RiverParameters;
+
+/// Pairs of cells across the map that reach each other, from near the corners to the opposite ones.
+fn far_apart_pairs(game_map: &GameMap, path_finder: &PathFinder) -> Vec<(UVec2, UVec2)> {
+    let (width, height) = (game_map.width(), game_map.height());
+    let walkable = |x: u32, y: u32| game_map.movement_modifier(x, y) > 0.0;
+    let mut pairs = vec![];
+    for step in 0..width / 8 {
+        let from = UVec2::new(step, step * 3 % (height / 4));
+        let to = UVec2::new(width - 1 - step * 2 % (width / 4), height - 1 - step);
+        if walkable(from.x, from.y) && walkable(to.x, to.y) && path_finder.is_reachable(from, to) {
+            pairs.push((from, to));
+        }
+    }
+    pairs
+}
+
+fn pathfinding(c: &mut Criterion) {
+    let json = std::fs::read_to_string("assets/biomes/details.biomes.json").unwrap();
+    let biomes = serde_json::from_str::<Biomes>(&json).unwrap().biomes;
+
+    let mut group = c.benchmark_group("pathfinding");
+    group.sample_size(10);
+    for size in [300, 600] {
+        let parameters = MapParameters {
+            width: size,
+            height: size,
+            seed: 7,
+            biome_weights: vec![1.0; biomes.len()],
+            rivers: RiverParameters { count: 8, ..Default::default() },
+        };
+        let cells = generate_map(&biomes, &parameters);
+        let game_map = GameMap::new(size, size, 7, biomes.clone(), cells.iter().map(|cell| cell.biome_id).collect());
+        let path_finder = PathFinder::new(&game_map);
+        let pairs = far_apart_pairs(&game_map, &path_finder);
+
+        let label = format!("{size}x{size}");
+        group.bench_with_input(BenchmarkId::new("flat", &label), &pairs, |b, pairs| {
+            b.iter(|| pairs.iter().filter_map(|(from, to)| path_finder.find_path_flat(*from, *to)).map(|path| path.cells.len()).sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("hierarchical", &label), &pairs, |b, pairs| {
+            b.iter(|| pairs.iter().filter_map(|(from, to)| path_finder.find_path(*from, *to)).map(|path| path.cells.len()).sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("reachable", &label), &pairs, |b, pairs| {
+            b.iter(|| pairs.iter().filter(|(from, to)| path_finder.is_reachable(*from, *to)).count())
+        });
+        group.bench_with_input(BenchmarkId::new("build", &label), &game_map, |b, game_map| {
+            b.iter(|| PathFinder::new(game_map))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pathfinding);
+criterion_main!(benches);
@@ -0,0 +1,199 @@
+//! Runs the game's map generation outside of the game and exports the result, for tuning biome tables.
+//!
+//! Usage: `cargo run --bin mapgen -- [--seed N] [--width N] [--height N] [--rivers N] [--biomes PATH]... [--out DIR]`
+//!
+//! Every `--biomes` table is merged into one registry, like the game does with the tables it loads.
+//!
+//! Writes `biomes.png` (one color per biome, magenta where no biome matched), grayscale `height.png`, `moisture.png` and `heat.png`,
+//! and `summary.json` with the coverage of every biome.
+
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use image::{GrayImage, Luma, Rgb, RgbImage};
+use meadowland::resources::biome_registry::{BiomeId, BiomeRegistry};
+use meadowland::resources::biomes::Biomes;
+use meadowland::systems::map_generation::{generate_map, MapGenerationCell, MapParameters};
+use meadowland::systems::rivers::RiverParameters;
+use serde::Serialize;
+
+type CellValue = fn(&MapGenerationCell) -> f64;
+
+struct Options {
+    seed: u64,
+    width: u32,
+    height: u32,
+    rivers: u32,
+    biomes: Vec<PathBuf>,
+    out: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    seed: u64,
+    width: u32,
+    height: u32,
+    biomes: Vec<BiomeCoverage>,
+    unmatched_cells: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BiomeCoverage {
+    name: String,
+    cells: usize,
+    percentage: f64,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("mapgen: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let options = parse_options(std::env::args().skip(1))?;
+
+    let mut tables = vec![];
+    for path in &options.biomes {
+        let biomes_json = fs::read_to_string(path).map_err(|error| format!("reading {}: {error}", path.display()))?;
+        let biomes: Biomes = serde_json::from_str(&biomes_json)
+            .map_err(|error| format!("parsing {}: {error}", path.display()))?;
+        tables.push((path.as_path(), biomes));
+    }
+    let biome_registry = BiomeRegistry::from_tables(tables.iter().map(|(path, biomes)| (*path, biomes)))
+        .map_err(|errors| format!("invalid biomes: {}", errors.join("; ")))?;
+    let biomes = biome_registry.biomes();
+
+    let parameters = MapParameters {
+        width: options.width,
+        height: options.height,
+        seed: options.seed,
+        biome_weights: vec![1.0; biomes.len()],
+        rivers: RiverParameters { count: options.rivers, ..Default::default() },
+    };
+    let cells = generate_map(biomes, &parameters);
+
+    fs::create_dir_all(&options.out)
+        .map_err(|error| format!("creating {}: {error}", options.out.display()))?;
+
+    let biome_image = RgbImage::from_fn(options.width, options.height, |x, y| {
+        let cell = cell_at(&cells, &options, x, y);
+        match cell.matched {
+            true => biome_color(cell.biome_id),
+            false => Rgb([255, 0, 255]),
+        }
+    });
+    save(&biome_image, &options, "biomes.png")?;
+
+    let layers: [(&str, CellValue); 3] = [
+        ("height.png", |cell| cell.height),
+        ("moisture.png", |cell| cell.moisture),
+        ("heat.png", |cell| cell.heat),
+    ];
+    for (file_name, value) in layers {
+        let layer_image = GrayImage::from_fn(options.width, options.height, |x, y| {
+            Luma([(value(cell_at(&cells, &options, x, y)) * 255.0).round() as u8])
+        });
+        save(&layer_image, &options, file_name)?;
+    }
+
+    let summary = Summary {
+        seed: options.seed,
+        width: options.width,
+        height: options.height,
+        biomes: biome_registry
+            .iter()
+            .map(|(biome_id, biome)| {
+                let count = cells.iter().filter(|cell| cell.matched && cell.biome_id == biome_id).count();
+                BiomeCoverage {
+                    name: biome.name.clone(),
+                    cells: count,
+                    percentage: count as f64 * 100.0 / cells.len() as f64,
+                }
+            })
+            .collect(),
+        unmatched_cells: cells.iter().filter(|cell| !cell.matched).count(),
+    };
+    let summary_path = options.out.join("summary.json");
+    let summary_json = serde_json::to_string_pretty(&summary).map_err(|error| error.to_string())?;
+    fs::write(&summary_path, summary_json)
+        .map_err(|error| format!("writing {}: {error}", summary_path.display()))?;
+
+    for coverage in &summary.biomes {
+        println!("{:>12}: {:6.2}%", coverage.name, coverage.percentage);
+    }
+    println!("Wrote the maps for seed {} to {}", options.seed, options.out.display());
+
+    Ok(())
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        seed: rand::random(),
+        width: 300,
+        height: 300,
+        rivers: 8,
+        biomes: vec![],
+        out: PathBuf::from("mapgen"),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--seed" => options.seed = value()?.parse().map_err(|_| "--seed expects a number")?,
+            "--width" => options.width = value()?.parse().map_err(|_| "--width expects a number")?,
+            "--height" => options.height = value()?.parse().map_err(|_| "--height expects a number")?,
+            "--rivers" => options.rivers = value()?.parse().map_err(|_| "--rivers expects a number")?,
+            "--biomes" => options.biomes.push(value()?.into()),
+            "--out" => options.out = value()?.into(),
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+
+    if options.width == 0 || options.height == 0 {
+        return Err("--width and --height have to be at least 1".to_string());
+    }
+    if options.biomes.is_empty() {
+        options.biomes.push(PathBuf::from("assets/biomes/details.biomes.json"));
+    }
+
+    Ok(options)
+}
+
+fn cell_at<'a>(cells: &'a [MapGenerationCell], options: &Options, x: u32, y: u32) -> &'a MapGenerationCell {
+    // images go top to bottom, the map grid goes bottom to top
+    &cells[((options.height - 1 - y) * options.width + x) as usize]
+}
+
+/// Spreads the biome colors around the hue wheel so neighboring ids stay distinguishable.
+fn biome_color(biome_id: BiomeId) -> Rgb<u8> {
+    let hue = (biome_id.0 as f64 * 0.618_033_988_75).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |value: f64| (55.0 + value * 200.0) as u8;
+    Rgb([channel(r), channel(g), channel(b)])
+}
+
+fn save<P, C>(image: &image::ImageBuffer<P, C>, options: &Options, file_name: &str) -> Result<(), String>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: image::EncodableLayout,
+    C: std::ops::Deref<Target = [P::Subpixel]>,
+{
+    let path = options.out.join(file_name);
+    image
+        .save(&path)
+        .map_err(|error| format!("writing {}: {error}", path.display()))
+}
@@ -0,0 +1,151 @@
+//! Plays a 1000x1000 map with thousands of trees while the camera sweeps across it, then prints the frame times.
+//!
+//! Usage: `cargo run --release --bin render_benchmark`
+//!
+//! Runs without vsync or a frame cap, so the frame times show what rendering the world costs.
+
+use std::f32::consts::TAU;
+
+use ::bevy_egui::EguiPlugin;
+use bevy::{app::AppExit, prelude::*};
+use bevy_ecs_tilemap::TilemapPlugin;
+use bevy_mod_picking::prelude::*;
+use bevy_prototype_lyon::plugin::ShapePlugin;
+use meadowland::bundles::plant_bundle::spawn_plant;
+use meadowland::plugins::{game_ui_plugin::*, game_world_plugin::*};
+use meadowland::resources::game_map::GameMap;
+use meadowland::resources::new_game::NewGameSettings;
+use meadowland::resources::settings::{Settings, WindowModeSetting};
+use meadowland::resources::spatial_index::SpatialIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const MAP_SIZE: u32 = 1000;
+const TREE_COUNT: usize = 10_000;
+/// Seconds to wait after the map shows up before measuring, while textures finish uploading.
+const WARMUP: f32 = 3.0;
+const DURATION: f32 = 20.0;
+/// Seconds for the camera to go around the map once.
+const SWEEP_PERIOD: f32 = 10.0;
+
+#[derive(Resource, Default)]
+struct FrameTimes {
+    main_entered_at: Option<f32>,
+    seconds: Vec<f32>,
+}
+
+fn main() {
+    let settings = Settings {
+        window_mode: WindowModeSetting::Windowed,
+        vsync: false,
+        frame_cap: None,
+        ..default()
+    };
+
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Meadowland render benchmark".to_string(),
+                present_mode: settings.present_mode(),
+                mode: settings.window_mode(),
+                resolution: settings.window_resolution(),
+                ..default()
+            }),
+            ..default()
+        }))
+        .insert_resource(settings)
+        .add_plugins(EguiPlugin)
+        .add_plugins(DefaultPickingPlugins.build()
+            .disable::<DefaultHighlightingPlugin>()
+            .disable::<SelectionPlugin>()
+            .disable::<DebugPickingPlugin>())
+        .add_plugins(ShapePlugin)
+        .add_plugins(TilemapPlugin)
+        .add_plugins(GameWorldPlugin)
+        .add_plugins(GameUiPlugin)
+        .insert_resource(NewGameSettings {
+            width: MAP_SIZE,
+            height: MAP_SIZE,
+            seed: 1,
+            pawn_count: NewGameSettings::MAX_PAWN_COUNT,
+            ..default()
+        })
+        .init_resource::<FrameTimes>()
+        .add_systems(Startup, setup)
+        .add_systems(OnEnter(GameState::MainMenu), start_new_game)
+        .add_systems(OnEnter(GameState::Main), spawn_trees)
+        .add_systems(Update, (sweep_camera, record_frame_times).run_if(in_state(GameState::Main)))
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn start_new_game(mut app_next_state: ResMut<NextState<GameState>>) {
+    app_next_state.set(GameState::MapGeneration);
+}
+
+fn spawn_trees(
+    mut commands: Commands,
+    game_world: Res<GameWorld>,
+    asset_server: Res<AssetServer>,
+    game_map: Res<GameMap>,
+    mut spatial_index: ResMut<SpatialIndex>,
+) {
+    let mut rng = StdRng::seed_from_u64(1);
+    let half_size = MAP_SIZE as f32 / 2.0;
+    // spots trees can't grow on are skipped, until there are as many as asked for
+    let mut index = 0;
+    while index < TREE_COUNT {
+        let name = if index % 2 == 0 { "tree-leafy" } else { "tree-pine" };
+        let x = rng.gen_range(-half_size..half_size).round();
+        let y = rng.gen_range(-half_size..half_size).round();
+        if spawn_plant(&mut commands, &game_world, &asset_server, &game_map, &mut spatial_index, name, Vec2::new(x, y)).is_ok() {
+            index += 1;
+        }
+    }
+}
+
+/// Circles the camera around the middle of the map, so chunks keep coming into and going out of view.
+fn sweep_camera(
+    time: Res<Time>,
+    game_world: Res<GameWorld>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let radius = MAP_SIZE as f32 * game_world.cell_size() as f32 / 3.0;
+    let angle = time.elapsed_seconds() / SWEEP_PERIOD * TAU;
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = angle.cos() * radius;
+        transform.translation.y = angle.sin() * radius;
+    }
+}
+
+fn record_frame_times(
+    time: Res<Time>,
+    mut frame_times: ResMut<FrameTimes>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let now = time.elapsed_seconds();
+    let main_entered_at = *frame_times.main_entered_at.get_or_insert(now);
+    if now - main_entered_at < WARMUP {
+        return;
+    }
+    frame_times.seconds.push(time.delta_seconds());
+    if now - main_entered_at < WARMUP + DURATION {
+        return;
+    }
+
+    let mut seconds = std::mem::take(&mut frame_times.seconds);
+    seconds.sort_by(f32::total_cmp);
+    let percentile = |fraction: f32| seconds[((seconds.len() - 1) as f32 * fraction) as usize] * 1000.0;
+    let mean = seconds.iter().sum::<f32>() / seconds.len() as f32 * 1000.0;
+    println!(
+        "{} frames, mean {mean:.2} ms, median {:.2} ms, 95th percentile {:.2} ms, worst {:.2} ms",
+        seconds.len(),
+        percentile(0.5),
+        percentile(0.95),
+        percentile(1.0),
+    );
+    app_exit_events.send(AppExit::Success);
+}
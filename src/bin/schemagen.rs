@@ -0,0 +1,37 @@
+//! Regenerates the JSON schemas under `schemas/` from the game's data types, run it after changing them.
+//!
+//! Usage: `cargo run --bin schemagen -- [--out DIR]`
+
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use meadowland::plugins::validated_json_asset_plugin::generated_schemas;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("schemagen: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut out = PathBuf::from("schemas");
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = args.next().ok_or("missing value for --out")?.into(),
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+
+    fs::create_dir_all(&out).map_err(|error| format!("creating {}: {error}", out.display()))?;
+    for (file_name, contents) in generated_schemas() {
+        let path = out.join(file_name);
+        fs::write(&path, contents).map_err(|error| format!("writing {}: {error}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+
+    Ok(())
+}
@@ -1,2 +1,3 @@
 pub mod pawn_bundle;
-pub mod plant_bundle;
\ No newline at end of file
+pub mod plant_bundle;
+pub mod resource_stack_bundle;
\ No newline at end of file
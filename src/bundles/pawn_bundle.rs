@@ -1,8 +1,10 @@
 use crate::{
-    components::{entity_selected::*, nickname::*, pawn::*, visual_aabb2d::*},
+    components::{entity_selected::*, nickname::*, pawn::*, tile_occupant::*, visual_aabb2d::*, work_priorities::*},
+    resources::{game_map::GameMap, spatial_index::SpatialIndex},
+    systems::occupancy::check_placement,
     GameWorld,
 };
-use bevy::{math::bounding::Aabb2d, prelude::*, render::view::NoFrustumCulling};
+use bevy::{math::bounding::Aabb2d, prelude::*};
 use bevy_mod_picking::prelude::*;
 
 #[derive(Bundle)]
@@ -13,17 +15,25 @@ struct PawnBundle {
     pub sprite: SpriteBundle,
     pub visual_aabb2d: VisualAabb2d,
     pub pickable: PickableBundle,
+    pub tile_occupant: TileOccupant,
+    pub work_priorities: WorkPriorities,
 }
 
+/// Spawns a pawn `position` cells from the middle of the map, unless it can't be placed there.
 pub fn spawn_pawn(
     commands: &mut Commands,
     game_world: &Res<GameWorld>,
     asset_server: &Res<AssetServer>,
+    game_map: &GameMap,
+    spatial_index: &mut SpatialIndex,
     name: &str,
-    x: f32,
-    y: f32,
-) {
-    commands
+    position: Vec2,
+) -> Result<Entity, String> {
+    let translation = (position * game_world.cell_size() as f32).extend(0.);
+    let cell = game_world.cell_at(translation.truncate()).ok_or("Can't place a pawn off the map")?;
+    check_placement(game_map, spatial_index, cell, TileOccupant::Pawn)?;
+
+    let entity = commands
         .spawn((
             PawnBundle {
                 nickname: Nickname(format!("Villager {}", name)),
@@ -33,10 +43,7 @@ pub fn spawn_pawn(
                         custom_size: Some(Vec2::splat(game_world.cell_size() as f32)),
                         ..default()
                     },
-                    transform: Transform {
-                        translation: Vec3::new(x, y, 0.) * game_world.cell_size() as f32,
-                        ..default()
-                    },
+                    transform: Transform::from_translation(translation),
                     ..default()
                 },
                 visual_aabb2d: VisualAabb2d(Aabb2d::new(
@@ -45,14 +52,15 @@ pub fn spawn_pawn(
                 )),
                 pickable: PickableBundle::default(),
                 pawn: Pawn,
+                tile_occupant: TileOccupant::Pawn,
+                work_priorities: WorkPriorities::default(),
                 name: Name::new("Pawn"),
             },
-            NoFrustumCulling,
             On::<Pointer<Click>>::run(select_pawn),
         ))
         .with_children(|parent| {
             // nameplate
-            parent.spawn((Text2dBundle {
+            parent.spawn(Text2dBundle {
                 text: Text {
                     sections: vec![TextSection {
                         value: name.to_string(),
@@ -70,9 +78,11 @@ pub fn spawn_pawn(
                     0.,
                 )),
                 ..default()
-            },
-            NoFrustumCulling));
-        });
+            });
+        })
+        .id();
+    spatial_index.insert(entity, cell, TileOccupant::Pawn);
+    Ok(entity)
 }
 
 pub fn select_pawn(
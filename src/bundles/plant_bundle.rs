@@ -1,12 +1,13 @@
-use bevy::{math::bounding::Aabb2d, prelude::*, render::view::NoFrustumCulling};
+use bevy::{math::bounding::Aabb2d, prelude::*};
 use bevy_mod_picking::prelude::*;
 
 use crate::{
     components::{
         entity_selected::*, entity_selected_actions::*, nickname::*, plant::*,
-        plant_harvest::PlantHarvest, visual_aabb2d::*,
+        plant_harvest::PlantHarvest, tile_occupant::*, visual_aabb2d::*,
     },
-    resources::jobs::*,
+    resources::{game_map::GameMap, jobs::*, spatial_index::SpatialIndex},
+    systems::occupancy::check_placement,
     GameWorld,
 };
 
@@ -18,17 +19,24 @@ struct PlantBundle {
     pub visual_aabb2d: VisualAabb2d,
     pub sprite: SpriteBundle,
     pub entity_selected_actions: EntitySelectedActions<'static>,
+    pub tile_occupant: TileOccupant,
 }
 
+/// Spawns a plant `position` cells from the middle of the map, unless it can't grow there.
 pub fn spawn_plant(
     commands: &mut Commands,
     game_world: &Res<GameWorld>,
     asset_server: &Res<AssetServer>,
+    game_map: &GameMap,
+    spatial_index: &mut SpatialIndex,
     name: &str,
-    x: f32,
-    y: f32,
-) {
-    commands
+    position: Vec2,
+) -> Result<Entity, String> {
+    let translation = (position * game_world.cell_size() as f32).extend(0.);
+    let cell = game_world.cell_at(translation.truncate()).ok_or("Can't place a plant off the map")?;
+    check_placement(game_map, spatial_index, cell, TileOccupant::Plant)?;
+
+    let entity = commands
         .spawn((
             PlantBundle {
                 nickname: Nickname(format!("Plant {}", name)),
@@ -38,10 +46,7 @@ pub fn spawn_plant(
                         custom_size: Some(Vec2::splat(game_world.cell_size() as f32)),
                         ..default()
                     },
-                    transform: Transform {
-                        translation: Vec3::new(x, y, 0.) * game_world.cell_size() as f32,
-                        ..default()
-                    },
+                    transform: Transform::from_translation(translation),
                     ..default()
                 },
                 visual_aabb2d: VisualAabb2d(Aabb2d::new(
@@ -52,6 +57,7 @@ pub fn spawn_plant(
                     name: name.to_string(),
                 },
                 name: Name::new(format!("Plant {}", name)),
+                tile_occupant: TileOccupant::Plant,
                 entity_selected_actions: EntitySelectedActions(vec![
                     EntitySelectedAction {
                         name: "Harvest".into(),
@@ -73,7 +79,6 @@ pub fn spawn_plant(
                     },
                 ]),
             },
-            NoFrustumCulling,
             On::<Pointer<Click>>::run(select_plant),
         ))
         .with_children(|parent| {
@@ -93,9 +98,11 @@ pub fn spawn_plant(
                     should_block_lower: false,
                     is_hoverable: false,
                 },
-                NoFrustumCulling,
             ));
-        });
+        })
+        .id();
+    spatial_index.insert(entity, cell, TileOccupant::Plant);
+    Ok(entity)
 }
 
 pub fn select_plant(
@@ -111,24 +118,24 @@ pub fn select_plant(
     commands.entity(entity_id).insert(EntitySelected);
 }
 
+/// Plants that were just marked for harvest.
+type HarvestMarked = (With<Plant>, Added<PlantHarvest>);
+
 pub fn update_plant_harvest_overlay(
     mut removed_harvest: RemovedComponents<PlantHarvest>,
-    plant_added_query: Query<(Entity, Ref<Children>), (With<Plant>, Added<PlantHarvest>)>,
+    plant_added_query: Query<(Entity, Ref<Children>), HarvestMarked>,
     plant_query: Query<(Entity, Ref<Children>), With<Plant>>,
     mut child_visibility_query: Query<&mut Visibility>,
     mut jobs: ResMut<Jobs>,
 ) {
     // added
     for (entity, children) in plant_added_query.iter() {
-        if let Some(child) = children.get(0) {
+        if let Some(child) = children.first() {
             if let Ok(mut visibility) = child_visibility_query.get_mut(*child) {
                 if *visibility != Visibility::Visible {
                     *visibility = Visibility::Visible;
 
-                    jobs.0.push(Job {
-                        name: "Plant Harvest".to_string(),
-                        job_type: JobType::PlantHarvest(entity),
-                    });
+                    jobs.post("Plant Harvest", JobType::PlantHarvest(entity));
                 }
             }
         }
@@ -139,14 +146,14 @@ pub fn update_plant_harvest_overlay(
         let Ok((entity, children)) = plant_query.get(entity) else {
             continue;
         };
-        if let Some(child) = children.get(0) {
+        if let Some(child) = children.first() {
             if let Ok(mut visibility) = child_visibility_query.get_mut(*child) {
                 if *visibility != Visibility::Hidden {
                     *visibility = Visibility::Hidden;
 
-                    jobs.0.retain(|job| match job.job_type {
-                        JobType::PlantHarvest(job_entity) => job_entity != entity,
-                    });
+                    if let Some(id) = jobs.find(&JobType::PlantHarvest(entity)).map(Job::id) {
+                        _ = jobs.cancel(id);
+                    }
                 }
             }
         }
@@ -0,0 +1,61 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+use std::path::Path;
+
+use crate::{
+    components::{nickname::*, resource_stack::*, tile_occupant::*, visual_aabb2d::*},
+    resources::{game_map::GameMap, spatial_index::SpatialIndex},
+    systems::occupancy::check_placement,
+    GameWorld,
+};
+
+#[derive(Bundle)]
+struct ResourceStackBundle {
+    pub resource_stack: ResourceStack,
+    pub name: Name,
+    pub nickname: Nickname,
+    pub visual_aabb2d: VisualAabb2d,
+    pub sprite: SpriteBundle,
+    pub tile_occupant: TileOccupant,
+}
+
+/// Drops `amount` of a resource on a map cell, unless it can't be placed there. A resource's texture
+/// sits next to its definition, `stone.resource.json` is drawn with `stone.resource.png`.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_resource_stack(
+    commands: &mut Commands,
+    game_world: &GameWorld,
+    asset_server: &AssetServer,
+    game_map: &GameMap,
+    spatial_index: &mut SpatialIndex,
+    definition_path: &Path,
+    resource: &str,
+    amount: u32,
+    cell: UVec2,
+) -> Result<Entity, String> {
+    check_placement(game_map, spatial_index, cell, TileOccupant::Item)?;
+
+    let entity = commands.spawn(ResourceStackBundle {
+        nickname: Nickname(format!("{resource} x{amount}")),
+        sprite: SpriteBundle {
+            texture: asset_server.load(definition_path.with_extension("png")),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(game_world.cell_size() as f32)),
+                ..default()
+            },
+            transform: Transform::from_translation(game_world.cell_center(cell).extend(0.)),
+            ..default()
+        },
+        visual_aabb2d: VisualAabb2d(Aabb2d::new(
+            Vec2::new(0., 0.),
+            Vec2::splat(game_world.cell_size() as f32),
+        )),
+        resource_stack: ResourceStack {
+            resource: resource.to_string(),
+            amount,
+        },
+        name: Name::new(format!("Resource {resource}")),
+        tile_occupant: TileOccupant::Item,
+    }).id();
+    spatial_index.insert(entity, cell, TileOccupant::Item);
+    Ok(entity)
+}
@@ -4,4 +4,14 @@ pub mod entity_selected;
 pub mod visual_aabb2d;
 pub mod plant;
 pub mod entity_selected_actions;
-pub mod plant_harvest;
\ No newline at end of file
+pub mod plant_harvest;
+pub mod light_source;
+pub mod map_chunk;
+pub mod transition_overlay;
+pub mod mine_designation;
+pub mod resource_stack;
+pub mod tile_occupant;
+pub mod obstruction;
+pub mod work_priorities;
+pub mod assigned_job;
+pub mod walk_path;
\ No newline at end of file
@@ -0,0 +1,6 @@
+use crate::resources::jobs::JobId;
+use bevy::prelude::*;
+
+/// The job a pawn reserved, until it's done, fails or the pawn lets go of it.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct AssignedJob(pub JobId);
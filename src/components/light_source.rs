@@ -0,0 +1,5 @@
+use crate::resources::light::Light;
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct LightSource(pub Light);
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+/// A tilemap holding one square of the map, its tile positions are relative to `origin`.
+#[derive(Component)]
+pub struct MapChunk {
+    /// The map cell of the chunk's first tile.
+    pub origin: UVec2,
+}
+
+impl MapChunk {
+    /// The width and height of a chunk, in cells. Chunks on the map's far edges can be smaller.
+    pub const SIZE: u32 = 64;
+
+    /// The chunks a map of `map_size` is split into, row by row.
+    pub fn covering(map_size: UVec2) -> impl Iterator<Item = MapChunk> {
+        (0..map_size.y).step_by(Self::SIZE as usize).flat_map(move |y| {
+            (0..map_size.x)
+                .step_by(Self::SIZE as usize)
+                .map(move |x| MapChunk { origin: UVec2::new(x, y) })
+        })
+    }
+
+    /// How many cells of a map of `map_size` the chunk holds.
+    pub fn size(&self, map_size: UVec2) -> UVec2 {
+        (map_size - self.origin).min(UVec2::splat(Self::SIZE))
+    }
+
+    /// The map cell a tile of this chunk sits on.
+    pub fn cell(&self, tile_pos: &TilePos) -> UVec2 {
+        self.origin + UVec2::new(tile_pos.x, tile_pos.y)
+    }
+}
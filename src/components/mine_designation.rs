@@ -0,0 +1,9 @@
+use bevy::prelude::*;
+
+/// Marks a rock cell for pawns to mine out, shown over the cell until it's done.
+#[derive(Component)]
+pub struct MineDesignation {
+    pub cell: UVec2,
+    /// How many seconds pawns have dug at the cell so far.
+    pub progress: f32,
+}
@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+/// Something on the map in the way of pawns, they walk through its cell slower, or not at all at 0.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Obstruction {
+    pub movement_modifier: f64,
+}
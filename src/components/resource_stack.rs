@@ -0,0 +1,9 @@
+use bevy::prelude::*;
+
+/// A pile of one resource lying on the map.
+#[derive(Component)]
+pub struct ResourceStack {
+    /// The name of the resource definition.
+    pub resource: String,
+    pub amount: u32,
+}
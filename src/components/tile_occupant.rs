@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+/// What kind of thing an entity on the map is, entities with it are kept in the `SpatialIndex` by the cell they're on.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TileOccupant {
+    Pawn,
+    Plant,
+    /// Things lying around to be picked up, like a `ResourceStack`.
+    Item,
+    Building,
+}
+
+impl TileOccupant {
+    /// Whether this kind can be on the same cell as `other`. Pawns walk over anything they can walk through,
+    /// items pile up and lie under plants, but plants and buildings each take a cell of their own.
+    pub fn can_share_cell_with(self, other: TileOccupant) -> bool {
+        matches!(
+            (self, other),
+            (TileOccupant::Pawn, _)
+                | (_, TileOccupant::Pawn)
+                | (TileOccupant::Item, TileOccupant::Item | TileOccupant::Plant)
+                | (TileOccupant::Plant, TileOccupant::Item)
+        )
+    }
+
+    /// The kind for messages, like "an item".
+    pub fn label(self) -> &'static str {
+        match self {
+            TileOccupant::Pawn => "a pawn",
+            TileOccupant::Plant => "a plant",
+            TileOccupant::Item => "an item",
+            TileOccupant::Building => "a building",
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+/// A tilemap drawn over a `MapChunk`, blending the edges of neighboring biomes over its tiles.
+#[derive(Component)]
+pub struct TransitionOverlay {
+    /// The map cell of the chunk's first tile, like `MapChunk::origin`.
+    pub origin: UVec2,
+    /// Overlays on higher layers draw over the lower ones.
+    pub layer: u32,
+}
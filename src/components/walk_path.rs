@@ -0,0 +1,5 @@
+use bevy::prelude::*;
+
+/// The cells a pawn still walks through to get where it's headed, the next one first.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct WalkPath(pub Vec<UVec2>);
@@ -0,0 +1,36 @@
+use crate::resources::jobs::WorkType;
+use bevy::prelude::*;
+
+/// How eager a pawn is to do each kind of work, from `HIGHEST` to `LOWEST`, `None` when it doesn't do it at all.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct WorkPriorities([Option<u8>; WorkType::ALL.len()]);
+
+impl WorkPriorities {
+    pub const HIGHEST: u8 = 1;
+    pub const LOWEST: u8 = 4;
+
+    pub fn get(&self, work_type: WorkType) -> Option<u8> {
+        self.0[work_type as usize]
+    }
+
+    /// Sets a priority, clamped to the range from `HIGHEST` to `LOWEST`.
+    pub fn set(&mut self, work_type: WorkType, priority: Option<u8>) {
+        self.0[work_type as usize] = priority.map(|priority| priority.clamp(Self::HIGHEST, Self::LOWEST));
+    }
+
+    /// The priority after `priority` when clicking through them: up to `LOWEST`, then none, then around again.
+    pub fn next(priority: Option<u8>) -> Option<u8> {
+        match priority {
+            Some(priority) if priority < Self::LOWEST => Some(priority + 1),
+            Some(_) => None,
+            None => Some(Self::HIGHEST),
+        }
+    }
+}
+
+/// Pawns do every kind of work, none before the others.
+impl Default for WorkPriorities {
+    fn default() -> Self {
+        WorkPriorities([Some(3); WorkType::ALL.len()])
+    }
+}
@@ -0,0 +1,7 @@
+use plugins::game_world_plugin::*;
+
+pub mod bundles;
+pub mod components;
+pub mod plugins;
+pub mod systems;
+pub mod resources;
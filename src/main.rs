@@ -1,24 +1,22 @@
 use ::bevy_egui::EguiPlugin;
 use bevy::prelude::*;
+use bevy_ecs_tilemap::TilemapPlugin;
 use bevy_inspector_egui::*;
 use bevy_mod_picking::prelude::*;
 use bevy_prototype_lyon::plugin::ShapePlugin;
-use plugins::game_world_plugin::*;
+use meadowland::plugins::{game_ui_plugin::*, game_world_plugin::*};
+use meadowland::resources::settings::Settings;
 use quick::WorldInspectorPlugin;
 
-pub mod bundles;
-pub mod components;
-pub mod plugins;
-pub mod systems;
-pub mod resources;
-
 fn main() {
+    let settings = Settings::load();
+
     App::new()
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Meadowland".to_string(),
-                present_mode: bevy::window::PresentMode::AutoVsync,
+                present_mode: settings.present_mode(),
                 window_theme: Some(bevy::window::WindowTheme::Dark),
                 enabled_buttons: bevy::window::EnabledButtons {
                     minimize: false,
@@ -26,13 +24,15 @@ fn main() {
                     ..default()
                 },
                 resizable: false,
-                mode: bevy::window::WindowMode::BorderlessFullscreen,
+                mode: settings.window_mode(),
+                resolution: settings.window_resolution(),
 
                 ..default()
             }),
             ..default()
         }))
-        .insert_resource(bevy_framepace::FramepaceSettings { limiter: bevy_framepace::Limiter::from_framerate(60.0) })
+        .insert_resource(bevy_framepace::FramepaceSettings { limiter: settings.frame_limiter() })
+        .insert_resource(settings)
         .add_plugins(bevy_framepace::FramepacePlugin)
         .add_plugins(EguiPlugin)
         .add_plugins(DefaultInspectorConfigPlugin)
@@ -42,8 +42,9 @@ fn main() {
             .disable::<SelectionPlugin>()
             .disable::<DebugPickingPlugin>())
         .add_plugins(ShapePlugin)
-        .insert_resource(GameWorld::new(300, 300))
+        .add_plugins(TilemapPlugin)
         .add_plugins(GameWorldPlugin)
+        .add_plugins(GameUiPlugin)
         .add_systems(Startup, setup)
         .run();
 }
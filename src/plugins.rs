@@ -1 +1,4 @@
-pub mod game_world_plugin;
\ No newline at end of file
+pub mod game_world_plugin;
+pub mod game_ui_plugin;
+pub mod headless_plugins;
+pub mod validated_json_asset_plugin;
\ No newline at end of file
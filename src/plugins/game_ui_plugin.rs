@@ -0,0 +1,109 @@
+use crate::components::{entity_selected::EntitySelected, visual_aabb2d::VisualAabb2d};
+use crate::plugins::game_world_plugin::GameState;
+use crate::resources::input_actions::ActionState;
+use crate::resources::settings::Settings;
+use crate::systems::camera::*;
+use crate::systems::input::*;
+use crate::systems::lighting::*;
+use crate::systems::menu::*;
+use crate::systems::mining::*;
+use crate::systems::settings::*;
+use crate::systems::ui::*;
+use bevy::{color::palettes::css::*, input::InputSystem, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+/// Everything that needs a window to be useful: the egui screens, the selection
+/// feedback and the lighting visuals. The game logic itself lives in `GameWorldPlugin`.
+pub struct GameUiPlugin;
+
+impl Plugin for GameUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>();
+        app.init_resource::<OptionsScreen>();
+        app.init_resource::<ActionState>();
+        app.init_resource::<WorkPrioritiesWindow>();
+
+        app.add_systems(Startup, create_light_glow_texture);
+        app.add_systems(Update, (apply_settings, apply_ui_scale));
+        app.add_systems(PreUpdate, update_action_state.after(InputSystem));
+        app.add_systems(Update, report_binding_conflicts);
+        app.add_systems(Update, (toggle_options_screen, capture_rebinding, run_options_ui).chain());
+
+        app.add_systems(Update, run_loading_ui
+            .run_if(in_state(GameState::Loading).or_else(in_state(GameState::MapGeneration))));
+
+        // before the options screen, so it opens on top of the menu
+        app.add_systems(Update, run_main_menu_ui
+            .before(run_options_ui)
+            .run_if(in_state(GameState::MainMenu)));
+        app.add_systems(Update, run_new_game_ui
+            .run_if(in_state(GameState::NewGame)));
+
+        app.add_systems(OnEnter(GameState::Main), (create_visual_selection_feedback, create_ambient_overlay));
+        app.add_systems(Update, update_visual_selection_feedback
+            .after(TransformSystem::TransformPropagate)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, run_main_ui
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (toggle_work_priorities_window, run_work_priorities_ui)
+            .chain()
+            .after(run_main_ui)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (pan_camera, zoom_camera, designate_mining_under_cursor)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (update_ambient_overlay, update_light_glows, spawn_light_glows)
+            .after(advance_day_cycle)
+            .after(attach_plant_lights)
+            .run_if(in_state(GameState::Main)));
+    }
+}
+
+#[derive(Component)]
+struct VisualSelectionFeedback;
+
+fn create_visual_selection_feedback(mut commands: Commands) {
+    commands.spawn((
+        ShapeBundle {
+            spatial: SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ..default()
+        },
+        Stroke::new(WHITE, 2.0),
+        VisualSelectionFeedback,
+    ));
+}
+
+fn update_visual_selection_feedback(
+    selection_query: Query<(&VisualAabb2d, &GlobalTransform), With<EntitySelected>>,
+    mut visual_selection_feedback_query: Query<(&mut Path, &mut Visibility, &mut Transform), With<VisualSelectionFeedback>>,
+    mut last_visual_selection_feedback_shape_size: Local<Vec2>,
+) {
+    match selection_query.iter().next() {
+        Some((VisualAabb2d(aabb2d), selection_global_transform)) => {
+            for (mut path, mut visibility, mut transform) in visual_selection_feedback_query.iter_mut() {
+                *visibility = Visibility::Visible;
+                transform.translation = selection_global_transform.translation();
+
+                let size = aabb2d.max - aabb2d.min;
+                if *last_visual_selection_feedback_shape_size != size {
+                    *last_visual_selection_feedback_shape_size = size;
+
+                    // build the path from shape
+                    let shape = shapes::Rectangle {
+                        extents: size / 2.0,
+                        ..default()
+                    };
+
+                    *path = GeometryBuilder::build_as(&shape);
+                }
+            }
+        }
+        None => {
+            for (_, mut visibility, _) in visual_selection_feedback_query.iter_mut() {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
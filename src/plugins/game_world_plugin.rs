@@ -1,30 +1,36 @@
 use crate::bundles::pawn_bundle::*;
 use crate::bundles::plant_bundle::*;
-use crate::components::{entity_selected::EntitySelected, visual_aabb2d::VisualAabb2d};
+use crate::plugins::validated_json_asset_plugin::ValidatedJsonAssetPlugin;
+use crate::resources::biome_registry::BiomeRegistry;
 use crate::resources::biomes::Biome;
 use crate::resources::biomes::Biomes;
+use crate::resources::day_cycle::DayCycle;
+use crate::resources::game_errors::GameErrors;
+use crate::components::tile_occupant::TileOccupant;
+use crate::resources::game_map::{GameMap, MapCellsChanged};
 use crate::resources::game_resources::GameResource;
-use crate::resources::jobs::Jobs;
-use crate::systems::ui::*;
+use crate::resources::jobs::{JobEvent, Jobs};
+use crate::resources::mods::{ActiveMods, MODS_FOLDER};
+use crate::resources::new_game::NewGameSettings;
+use crate::resources::plants::PlantDefinition;
+use crate::resources::spatial_index::SpatialIndex;
+use crate::systems::lighting::*;
+use crate::systems::asset_loading::*;
+use crate::systems::hot_reload::*;
+use crate::systems::job_assignment::*;
 use crate::systems::map_generation::*;
-use bevy::asset::LoadedFolder;
-use bevy::{color::palettes::css::*, prelude::*};
-use bevy_prototype_lyon::prelude::*;
-use bevy_common_assets::json::JsonAssetPlugin;
-use rand::rngs::StdRng;
-use rand::SeedableRng;
-
-#[derive(Resource)]
+use crate::systems::mining::*;
+use crate::systems::occupancy::*;
+use crate::systems::path_finding::*;
+use crate::systems::spatial_index::*;
+use crate::systems::walking::*;
+use bevy::prelude::*;
+
+#[derive(Resource, Default)]
 pub struct GameWorld {
     height: u32,
     width: u32,
-    rng: StdRng,
-}
-
-impl Default for GameWorld {
-    fn default() -> Self {
-        Self { height: 0, width: 0, rng: StdRng::from_entropy() }
-    }
+    biome_textures: Vec<(String, Handle<Image>)>,
 }
 
 
@@ -41,24 +47,64 @@ impl GameWorld {
         32
     }
 
+    /// Where the center of a map cell is in the world, the map is centered on the origin.
+    pub fn cell_center(&self, cell: UVec2) -> Vec2 {
+        let map_center = (UVec2::new(self.width, self.height).as_vec2() - 1.0) / 2.0;
+        (cell.as_vec2() - map_center) * self.cell_size() as f32
+    }
+
+    /// The map cell under a point in the world, `None` off the map.
+    pub fn cell_at(&self, position: Vec2) -> Option<UVec2> {
+        let map_center = (UVec2::new(self.width, self.height).as_vec2() - 1.0) / 2.0;
+        let cell = (position / self.cell_size() as f32 + map_center).round();
+        let inside = cell.cmpge(Vec2::ZERO).all() && cell.cmplt(UVec2::new(self.width, self.height).as_vec2()).all();
+        inside.then(|| cell.as_uvec2())
+    }
+
     pub fn font_name(&self) -> String {
         "OpenSans-Regular.ttf".to_string()
     }
 
+    /// The textures for a biome's tiles, along with their index in `texture_handles_for_all_biomes`.
     pub fn texture_handles_for_biome(&self, biome: &Biome) -> Vec<(Handle<Image>, u32)> {
-        todo!()
+        self.biome_textures
+            .iter()
+            .enumerate()
+            .filter(|(_, (tile_name, _))| tile_name == biome.tile_name())
+            .map(|(index, (_, handle))| (handle.clone(), index as u32))
+            .collect()
     }
 
     pub fn texture_handles_for_all_biomes(&self) -> Vec<Handle<Image>> {
-        todo!()
+        self.biome_textures.iter().map(|(_, handle)| handle.clone()).collect()
     }
 
-    pub fn rng(&mut self) -> &mut StdRng {
-        &mut self.rng
+    /// Picks up the tile textures from the loaded assets, they are stored as `biomes/<tile name>/*.png`,
+    /// in the base game or in a mod's folder.
+    pub fn set_biome_textures(&mut self, handles: &[UntypedHandle]) {
+        self.biome_textures = handles
+            .iter()
+            .filter_map(|handle| {
+                let path = handle.path()?.path();
+                let mut components = path.iter();
+                if path.starts_with(MODS_FOLDER) {
+                    components.nth(1)?;
+                }
+                if components.next()? != "biomes" {
+                    return None;
+                }
+                let tile_name = components.next()?.to_str()?.to_string();
+                components.next()?;
+
+                Some((tile_name, handle.clone().try_typed::<Image>().ok()?))
+            })
+            .collect();
+        self.biome_textures.sort_by_key(|(_, handle)| handle.path().map(|path| path.to_string()));
     }
 
-    pub fn new(width: u32, height: u32) -> Self {
-        GameWorld { width, height, ..default() }
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
     }
 }
 
@@ -69,27 +115,55 @@ impl Plugin for GameWorldPlugin {
         app.init_state::<GameState>();
         app.init_resource::<GameWorld>();
         app.init_resource::<Jobs>();
-
-        app.add_plugins(JsonAssetPlugin::<GameResource>::new(&["resource.json"]));
-        app.add_plugins(JsonAssetPlugin::<Biomes>::new(&["biomes.json"]));
+        app.init_resource::<DayCycle>();
+        app.init_resource::<GameErrors>();
+        app.init_resource::<ActiveMods>();
+        app.init_resource::<BiomeRegistry>();
+        app.init_resource::<NewGameSettings>();
+        app.init_resource::<MapPreview>();
+        app.init_resource::<SpatialIndex>();
+
+        app.add_plugins(ValidatedJsonAssetPlugin::<GameResource>::new(&["resource.json"], "resource.json"));
+        app.add_plugins(ValidatedJsonAssetPlugin::<Biomes>::new(&["biomes.json"], "biome.json"));
+        app.add_plugins(ValidatedJsonAssetPlugin::<PlantDefinition>::new(&["plant.json"], "plant.json"));
         
+        app.add_event::<RetryFailedAssets>();
+        app.add_event::<ToggleMineDesignation>();
+        app.add_event::<MapCellsChanged>();
+        app.add_event::<JobEvent>();
         app.add_systems(Startup, start_load_assets);
 
-        app.add_systems(Update, check_assets_loaded.run_if(in_state(GameState::Loading)));
-        app.add_systems(Update, run_loading_ui
-            .run_if(in_state(GameState::Loading).or_else(in_state(GameState::MapGeneration))));
+        app.add_systems(Update, (check_asset_listing, check_assets_loaded, retry_failed_assets)
+            .chain()
+            .run_if(in_state(GameState::Loading)));
+
+        app.add_systems(OnEnter(GameState::MainMenu), register_biomes);
+
+        app.add_systems(Update, update_map_preview.run_if(in_state(GameState::NewGame)));
 
         app.add_systems(OnEnter(GameState::MapGeneration),start_map_generation);
         app.add_systems(Update, check_map_generation_finished.run_if(in_state(GameState::MapGeneration)));
 
-        app.add_systems(OnEnter(GameState::Main), (create_visual_selection_feedback, generate_world));
-        app.add_systems(Update, update_visual_selection_feedback
-            .after(TransformSystem::TransformPropagate)
-            .run_if(in_state(GameState::Main)));
+        app.add_systems(OnEnter(GameState::Main), (generate_world, setup_path_finder));
         app.add_systems(Update, update_plant_harvest_overlay
             .run_if(in_state(GameState::Main)));
-        app.add_systems(Update, run_main_ui
+        app.add_systems(Update, (apply_mine_designations, dig_designated_rock)
+            .chain()
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (attach_plant_obstructions, update_spatial_index, update_obstructions, update_path_finder, check_jobs, assign_jobs)
+            .chain()
+            .after(dig_designated_rock)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (clear_abandoned_designations, walk_to_designated_rock, walk_paths)
+            .chain()
+            .after(assign_jobs)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (advance_day_cycle, attach_plant_lights)
+            .run_if(in_state(GameState::Main)));
+        app.add_systems(Update, (apply_biome_changes, log_game_resource_changes)
             .run_if(in_state(GameState::Main)));
+        // after everything that changes jobs
+        app.add_systems(PostUpdate, send_job_events.run_if(in_state(GameState::Main)));
     }
 }
 
@@ -97,96 +171,60 @@ impl Plugin for GameWorldPlugin {
 pub enum GameState {
     #[default]
     Loading,
+    MainMenu,
+    /// Picking the map and the starting pawns.
+    NewGame,
     MapGeneration,
     Main,
 }
 
-#[derive(Resource)]
-struct LoadedFolderHandle(Handle<LoadedFolder>);
-
-fn start_load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(LoadedFolderHandle(asset_server.load_folder(".")));
-}
-
-fn check_assets_loaded(
-    mut app_next_state: ResMut<NextState<GameState>>,
-    mut events: EventReader<AssetEvent<LoadedFolder>>,
-){
-    for event in events.read() {
-        if let AssetEvent::LoadedWithDependencies { id: _} = event {
-            app_next_state.set(GameState::MapGeneration);
-        }
-    }
-}
+const PAWN_NAMES: [&str; 8] = ["Sana", "Mina", "Eunha", "Yuju", "SinB", "Umji", "Yerin", "Sowon"];
 
 fn generate_world(
     mut commands: Commands,
     game_world: Res<GameWorld>,
+    new_game_settings: Res<NewGameSettings>,
     asset_server: Res<AssetServer>,
+    game_map: Res<GameMap>,
+    mut spatial_index: ResMut<SpatialIndex>,
 ) {
-    // add a few villagers for testing
-    for w in vec![("Sana", 0, 0), ("Mina", 10, 2), ("Eunha", 8, 6)] {
-        spawn_pawn(&mut commands, &game_world, &asset_server, w.0, w.1 as f32, w.2 as f32);
+    // the first villagers keep their usual spots, the others line up to their right. A spot off the map
+    // or one a villager can't stand on moves it to the nearest one it can
+    for index in 0..new_game_settings.pawn_count as usize {
+        let name = match PAWN_NAMES.get(index) {
+            Some(name) => name.to_string(),
+            None => (index + 1).to_string(),
+        };
+        let (x, y) = match index {
+            0 => (0, 0),
+            1 => (10, 2),
+            2 => (8, 6),
+            _ => (12 + (index as i32 - 3) / 5 * 2, (index as i32 - 3) % 5 * 2),
+        };
+        let map_size = IVec2::new(game_map.width() as i32, game_map.height() as i32);
+        let wanted = (IVec2::new(x, y) + map_size / 2).clamp(IVec2::ZERO, map_size - 1).as_uvec2();
+        let Some(spot) = nearest_placement(&game_map, &spatial_index, wanted, TileOccupant::Pawn) else {
+            warn!("Found no spot for villager {name}");
+            continue;
+        };
+        let position = game_world.cell_center(spot) / game_world.cell_size() as f32;
+        if let Err(error) = spawn_pawn(&mut commands, &game_world, &asset_server, &game_map, &mut spatial_index, &name, position) {
+            warn!("{error}");
+        }
     }
 
-    // and a few trees
+    // and a few trees, where they can grow
     for x in 2..5 {
         for y in 2..7 {
-            spawn_plant(&mut commands, &game_world, &asset_server, "tree-leafy", x as f32, y as f32);
+            if let Err(error) = spawn_plant(&mut commands, &game_world, &asset_server, &game_map, &mut spatial_index, "tree-leafy", Vec2::new(x as f32, y as f32)) {
+                warn!("{error}");
+            }
         }
     }
     for x in -6..4 {
         for y in -10..-4 {
-            spawn_plant(&mut commands, &game_world, &asset_server, "tree-pine", x as f32, y as f32);
-        }
-    }
-}
-
-#[derive(Component)]
-struct VisualSelectionFeedback;
-
-fn create_visual_selection_feedback(mut commands: Commands) {
-    commands.spawn((
-        ShapeBundle {
-            spatial: SpatialBundle {
-                visibility: Visibility::Hidden,
-                ..default()
-            },
-            ..default()
-        },
-        Stroke::new(WHITE, 2.0),
-        VisualSelectionFeedback,
-    ));
-}
-
-fn update_visual_selection_feedback(
-    selection_query: Query<(&VisualAabb2d, &GlobalTransform), With<EntitySelected>>,
-    mut visual_selection_feedback_query: Query<(&mut Path, &mut Visibility, &mut Transform), With<VisualSelectionFeedback>>,
-    mut last_visual_selection_feedback_shape_size: Local<Vec2>,
-) {
-    match selection_query.iter().next() {
-        Some((VisualAabb2d(aabb2d), selection_global_transform)) => {
-            for (mut path, mut visibility, mut transform) in visual_selection_feedback_query.iter_mut() {
-                *visibility = Visibility::Visible;
-                transform.translation = selection_global_transform.translation();
-
-                let size = aabb2d.max - aabb2d.min;
-                if *last_visual_selection_feedback_shape_size != size {
-                    *last_visual_selection_feedback_shape_size = size;
-
-                    // build the path from shape
-                    let shape = shapes::Rectangle {
-                        extents: size / 2.0,
-                        ..default()
-                    };
-
-                    *path = GeometryBuilder::build_as(&shape);
-                }
-            }
-        }
-        None => {
-            for (_, mut visibility, _) in visual_selection_feedback_query.iter_mut() {
-                *visibility = Visibility::Hidden;
+            if let Err(error) = spawn_plant(&mut commands, &game_world, &asset_server, &game_map, &mut spatial_index, "tree-pine", Vec2::new(x as f32, y as f32)) {
+                warn!("{error}");
             }
         }
     }
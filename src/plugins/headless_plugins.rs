@@ -0,0 +1,30 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::text::FontLoader;
+
+/// `MinimalPlugins` plus what `GameWorldPlugin` needs to load assets, generate the
+/// map and run the world, without opening a window or touching the GPU.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        MinimalPlugins
+            .build()
+            .add(TransformPlugin)
+            .add(HierarchyPlugin)
+            .add(AssetPlugin::default())
+            .add(ImagePlugin::default())
+            .add(HeadlessFontPlugin)
+            .add(StatesPlugin)
+    }
+}
+
+/// Fonts are only needed so nameplates can hold their handles, `TextPlugin` itself wants a renderer.
+struct HeadlessFontPlugin;
+
+impl Plugin for HeadlessFontPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Font>().init_asset_loader::<FontLoader>();
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use crate::resources::biomes::Biomes;
+use crate::resources::game_resources::{GameResource, ResourceQuantity};
+use crate::resources::light::{Light, LightColor};
+use crate::resources::mods::ModManifest;
+use crate::resources::plants::PlantDefinition;
+use crate::resources::range::Range;
+use bevy::prelude::*;
+use jsonschema::{Resource, Validator};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The shipped schemas, by file name. Schemas reference each other by these names.
+const SCHEMAS: &[(&str, &str)] = &[
+    ("biome.json", include_str!("../../schemas/biome.json")),
+    ("color.json", include_str!("../../schemas/color.json")),
+    ("light.json", include_str!("../../schemas/light.json")),
+    ("mod.json", include_str!("../../schemas/mod.json")),
+    ("plant.json", include_str!("../../schemas/plant.json")),
+    ("range.json", include_str!("../../schemas/range.json")),
+    ("resource.json", include_str!("../../schemas/resource.json")),
+    ("resource_quantity.json", include_str!("../../schemas/resource_quantity.json")),
+];
+
+/// The schemas generated from the types the game deserializes, by file name, as they're checked in under `schemas/`.
+pub fn generated_schemas() -> Vec<(&'static str, String)> {
+    vec![
+        ("biome.json", generate_schema::<Biomes>()),
+        ("color.json", generate_schema::<LightColor>()),
+        ("light.json", generate_schema::<Light>()),
+        ("mod.json", generate_schema::<ModManifest>()),
+        ("plant.json", generate_schema::<PlantDefinition>()),
+        ("range.json", generate_schema::<Range>()),
+        ("resource.json", generate_schema::<GameResource>()),
+        ("resource_quantity.json", generate_schema::<ResourceQuantity>()),
+    ]
+}
+
+fn generate_schema<T: JsonSchema>() -> String {
+    // optional fields are left out rather than set to null in the data files
+    let generator = SchemaSettings::draft07()
+        .with(|settings| settings.option_add_null_type = false)
+        .into_generator();
+    let schema = generator.into_root_schema_for::<T>();
+    serde_json::to_string_pretty(&schema).expect("schemas serialize") + "\n"
+}
+
+/// Base URI relative `$ref`s resolve against when a schema doesn't declare its own `$id`.
+const SCHEMA_BASE_URI: &str = "json-schema:///";
+
+/// Builds a validator for one of the shipped schemas, with the other schemas available to its `$ref`s.
+pub fn schema_validator(schema_name: &str) -> Result<Validator, String> {
+    let parse = |name: &str, contents: &str| {
+        serde_json::from_str::<Value>(contents).map_err(|error| format!("Schema {name} is not valid JSON: {error}"))
+    };
+
+    let (_, contents) = SCHEMAS
+        .iter()
+        .find(|(name, _)| *name == schema_name)
+        .ok_or_else(|| format!("There is no schema named {schema_name}"))?;
+    let schema = parse(schema_name, contents)?;
+
+    let mut options = jsonschema::options();
+    for (name, contents) in SCHEMAS {
+        let resource = Resource::from_contents(parse(name, contents)?)
+            .map_err(|error| format!("Schema {name} could not be used: {error}"))?;
+        options.with_resource(format!("{SCHEMA_BASE_URI}{name}"), resource);
+    }
+
+    options
+        .build(&schema)
+        .map_err(|error| format!("Schema {schema_name} could not be compiled: {error}"))
+}
+
+/// Every way `value` breaks the schema, each prefixed with the JSON pointer to the offending value.
+pub fn schema_violations(validator: &Validator, value: &Value) -> Vec<String> {
+    validator
+        .iter_errors(value)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            format!("{}: {error}", if path.is_empty() { "/" } else { &path })
+        })
+        .collect()
+}
+
+/// Like `JsonAssetPlugin`, but checks every file against its schema before deserializing it.
+pub struct ValidatedJsonAssetPlugin<A> {
+    extensions: Vec<&'static str>,
+    schema_name: &'static str,
+    _marker: PhantomData<A>,
+}
+
+impl<A> ValidatedJsonAssetPlugin<A>
+where
+    A: DeserializeOwned + Asset,
+{
+    pub fn new(extensions: &[&'static str], schema_name: &'static str) -> Self {
+        Self {
+            extensions: extensions.to_owned(),
+            schema_name,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A> Plugin for ValidatedJsonAssetPlugin<A>
+where
+    A: DeserializeOwned + Asset,
+{
+    fn build(&self, app: &mut App) {
+        // the schemas are compiled into the game, a broken one is a bug rather than bad data
+        let validator = schema_validator(self.schema_name).unwrap_or_else(|error| panic!("{error}"));
+
+        app.init_asset::<A>().register_asset_loader(ValidatedJsonAssetLoader::<A> {
+            extensions: self.extensions.clone(),
+            validator: Arc::new(validator),
+            _marker: PhantomData,
+        });
+    }
+}
+
+pub struct ValidatedJsonAssetLoader<A> {
+    extensions: Vec<&'static str>,
+    validator: Arc<Validator>,
+    _marker: PhantomData<A>,
+}
+
+#[derive(Debug, Error)]
+pub enum ValidatedJsonLoaderError {
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse the JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The file doesn't match its schema: {}", .0.join("; "))]
+    Schema(Vec<String>),
+}
+
+impl<A> AssetLoader for ValidatedJsonAssetLoader<A>
+where
+    A: DeserializeOwned + Asset,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = ValidatedJsonLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let value: Value = serde_json::from_slice(&bytes)?;
+        let violations = schema_violations(&self.validator, &value);
+        if !violations.is_empty() {
+            return Err(ValidatedJsonLoaderError::Schema(violations));
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
@@ -1,3 +1,16 @@
 pub mod jobs;
 pub mod game_resources;
-pub mod biomes;
\ No newline at end of file
+pub mod biomes;
+pub mod biome_registry;
+pub mod light;
+pub mod plants;
+pub mod day_cycle;
+pub mod game_errors;
+pub mod game_map;
+pub mod mods;
+pub mod settings;
+pub mod input_actions;
+pub mod new_game;
+pub mod path_finder;
+pub mod spatial_index;
+pub mod range;
\ No newline at end of file
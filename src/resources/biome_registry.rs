@@ -0,0 +1,121 @@
+use crate::resources::biomes::{Biome, BiomeKind, Biomes};
+use crate::resources::mods::mod_folder_of;
+use bevy::prelude::*;
+use std::path::Path;
+
+/// A biome's place in the registry, it never changes for the rest of the game, even when biome tables are edited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BiomeId(pub u16);
+
+impl BiomeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Every biome of every loaded `*.biomes.json`, merged into one table.
+///
+/// Tables merge in load order: the base game's by path, then every mod's. A biome keeps the id of its
+/// first definition. A mod defining a biome again by name overrides it, while the same name in two files
+/// of the same layer (the base game, or a single mod) is an error, since neither clearly wins.
+#[derive(Resource, Default)]
+pub struct BiomeRegistry {
+    biomes: Vec<Biome>,
+}
+
+impl BiomeRegistry {
+    /// Merges `tables`, given in load order along with the path they were loaded from, and validates the result.
+    pub fn from_tables<'a>(tables: impl IntoIterator<Item = (&'a Path, &'a Biomes)>) -> Result<Self, Vec<String>> {
+        let mut biomes: Vec<Biome> = vec![];
+        let mut defined_in: Vec<&Path> = vec![];
+        let mut errors = vec![];
+
+        for (path, table) in tables {
+            for biome in &table.biomes {
+                let Some(index) = biomes.iter().position(|existing| existing.name == biome.name) else {
+                    biomes.push(biome.clone());
+                    defined_in.push(path);
+                    continue;
+                };
+
+                let previous_path = defined_in[index];
+                if previous_path == path {
+                    errors.push(format!("Biome {} is defined more than once in {}", biome.name, path.display()));
+                } else if mod_folder_of(previous_path) == mod_folder_of(path) {
+                    errors.push(format!(
+                        "Biome {} is defined in both {} and {}",
+                        biome.name,
+                        previous_path.display(),
+                        path.display()
+                    ));
+                } else {
+                    info!("{} overrides biome {} from {}", path.display(), biome.name, previous_path.display());
+                    biomes[index] = biome.clone();
+                    defined_in[index] = path;
+                }
+            }
+        }
+
+        // the river pass carves a single biome, checked over every table since each of them could bring one
+        let river_biomes: Vec<_> = biomes
+            .iter()
+            .zip(&defined_in)
+            .filter(|(biome, _)| biome.kind == BiomeKind::River)
+            .map(|(biome, path)| format!("{} from {}", biome.name, path.display()))
+            .collect();
+        if river_biomes.len() > 1 {
+            errors.push(format!("Only one biome can be a river, but {} are", river_biomes.join(", ")));
+        }
+
+        if biomes.len() > u16::MAX as usize {
+            errors.push(format!("There are {} biomes, at most {} are supported", biomes.len(), u16::MAX));
+        }
+
+        let merged = Biomes { biomes };
+        if let Err(table_errors) = merged.validate() {
+            errors.extend(table_errors);
+        }
+
+        match errors.is_empty() {
+            true => Ok(BiomeRegistry { biomes: merged.biomes }),
+            false => Err(errors),
+        }
+    }
+
+    /// The biomes, each at the index of its id.
+    pub fn biomes(&self) -> &[Biome] {
+        &self.biomes
+    }
+
+    pub fn get(&self, id: BiomeId) -> &Biome {
+        &self.biomes[id.index()]
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<BiomeId> {
+        self.biomes
+            .iter()
+            .position(|biome| biome.name == name)
+            .map(|index| BiomeId(index as u16))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BiomeId, &Biome)> {
+        self.biomes.iter().enumerate().map(|(index, biome)| (BiomeId(index as u16), biome))
+    }
+
+    /// Takes the definitions from `reloaded` while keeping every id. Biomes that are no longer defined keep
+    /// their last definition, since the map may still use them, and new biomes get the next ids.
+    pub fn update(&mut self, reloaded: BiomeRegistry) {
+        for biome in &self.biomes {
+            if reloaded.id_of(&biome.name).is_none() {
+                warn!("Biome {} was removed from the biome tables, it keeps its old definition", biome.name);
+            }
+        }
+
+        for biome in reloaded.biomes {
+            match self.biomes.iter_mut().find(|existing| existing.name == biome.name) {
+                Some(existing) => *existing = biome,
+                None => self.biomes.push(biome),
+            }
+        }
+    }
+}
@@ -1,18 +1,266 @@
+use crate::resources::mods::NamedDefinition;
 use bevy::prelude::*;   
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Deserialize, Asset, TypePath, Clone, Debug)]
+/// A table of biomes, every map cell gets the closest biome whose thresholds it meets.
+#[derive(Deserialize, JsonSchema, Asset, TypePath, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Biomes {
+    #[schemars(length(min = 1))]
     pub biomes: Vec<Biome>,
 }
 
-#[derive(Deserialize, TypePath, Clone, Debug)]
+#[derive(Deserialize, JsonSchema, TypePath, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Biome {
+    /// The unique name of the biome.
     pub name: String,
+    /// The folder under biomes to take tile textures from, defaults to the biome's name.
+    pub tile_name: Option<String>,
+    /// How often each of the biome's tile textures is picked, textures that no entry matches weigh 1.
+    #[serde(default)]
+    pub tile_weights: Vec<TileWeight>,
+    /// The multiplier applied to the speed of anyone walking on this biome, 0 makes it impassable. Rock is
+    /// always impassable.
+    #[schemars(range(min = 0))]
     pub movement_modifier: f64,
+    /// How map generation places the biome, defaults to land.
+    #[serde(default)]
+    pub kind: BiomeKind,
+    /// The lowest height a cell needs to get this biome, defaults to 0.
+    #[serde(default)]
+    #[schemars(range(min = 0, max = 1))]
     pub min_height: f64,
+    /// The lowest moisture a cell needs to get this biome, defaults to 0.
+    #[serde(default)]
+    #[schemars(range(min = 0, max = 1))]
     pub min_moisture: f64,
+    /// The lowest heat a cell needs to get this biome, defaults to 0.
+    #[serde(default)]
+    #[schemars(range(min = 0, max = 1))]
     pub min_heat: f64,
-}
\ No newline at end of file
+    /// The plants that can grow on this biome.
+    #[serde(default)]
+    pub plants: Vec<BiomePlant>,
+    /// How the biome's tiles blend over the edges of neighboring biomes, without it they meet with hard edges.
+    pub transitions: Option<BiomeTransitions>,
+    /// What pawns get out of mining the biome, only rock can be mined.
+    pub mining: Option<BiomeMining>,
+    /// Makes the biome an ore vein running through another biome, placed by the ore noise instead of its thresholds.
+    pub vein: Option<OreVein>,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BiomeKind {
+    /// Placed where the cells meet its thresholds.
+    #[default]
+    Land,
+    /// Placed where the cells meet its thresholds, rivers flow until they reach it.
+    Water,
+    /// Only placed by rivers, its thresholds are ignored. A table has at most one.
+    River,
+    /// Placed where the cells meet its thresholds, solid rock that blocks movement until it's mined out.
+    Rock,
+    /// Only placed by mining out rock, its thresholds are ignored.
+    Floor,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BiomePlant {
+    /// The name of the plant definition.
+    pub name: String,
+    /// The chance for a cell of this biome to grow this plant.
+    #[schemars(range(min = 0, max = 1))]
+    pub chance: f64,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TileWeight {
+    /// The textures' file name without its extension, a `*` at the end matches every name starting with the rest.
+    pub texture: String,
+    /// How often the textures are picked compared to the others. A texture counts the first entry matching it.
+    #[schemars(range(min = 0))]
+    pub weight: f64,
+    /// Gathers the textures in patches about this many cells across instead of scattering them,
+    /// their weight is then what they average to.
+    #[schemars(range(min = 1))]
+    pub patch_size: Option<f64>,
+}
+
+impl TileWeight {
+    pub fn matches(&self, texture_name: &str) -> bool {
+        match self.texture.strip_suffix('*') {
+            Some(prefix) => texture_name.starts_with(prefix),
+            None => texture_name == self.texture,
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BiomeTransitions {
+    /// The biome blends over neighbors on a lower layer or without transitions, biomes on the same layer
+    /// meet with hard edges.
+    pub layer: i32,
+    /// How far the blend reaches into the neighboring tile, as a fraction of a tile, defaults to 0.5.
+    #[serde(default = "BiomeTransitions::default_reach")]
+    #[schemars(range(min = 0, max = 1))]
+    pub reach: f32,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BiomeMining {
+    /// The biome a mined out cell turns into, usually a floor.
+    pub mined_into: String,
+    /// How long a pawn digs at a cell before it's mined out, in seconds.
+    #[schemars(range(min = 0))]
+    pub work: f32,
+    /// The resources dropped on the cell once it's mined out.
+    #[serde(default)]
+    pub drops: Vec<ResourceDrop>,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDrop {
+    /// The name of the resource definition.
+    pub resource: String,
+    /// How many units of the resource are dropped.
+    #[schemars(range(min = 1))]
+    pub amount: u32,
+}
+
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OreVein {
+    /// The biome the veins run through, cells of any other biome never get the ore.
+    pub host: String,
+    /// About how much of the host the veins take up, from 0 to 1.
+    #[schemars(range(min = 0, max = 1))]
+    pub abundance: f64,
+}
+
+impl BiomeTransitions {
+    fn default_reach() -> f32 {
+        0.5
+    }
+}
+
+impl Biomes {
+    /// Checks the thresholds, names and fallback of the table, returning every problem found.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        if self.biomes.is_empty() {
+            errors.push("The biome table is empty".to_string());
+        }
+
+        for (index, biome) in self.biomes.iter().enumerate() {
+            if biome.name.trim().is_empty() {
+                errors.push(format!("Biome #{index} has no name"));
+            }
+            if self.biomes[..index].iter().any(|other| other.name == biome.name) {
+                errors.push(format!("Biome {} is defined more than once", biome.name));
+            }
+            if !biome.movement_modifier.is_finite() || biome.movement_modifier < 0.0 {
+                errors.push(format!("Biome {} has a negative movement modifier", biome.name));
+            }
+            for (threshold, value) in [
+                ("minHeight", biome.min_height),
+                ("minMoisture", biome.min_moisture),
+                ("minHeat", biome.min_heat),
+            ] {
+                if !(0.0..=1.0).contains(&value) {
+                    errors.push(format!("Biome {} has {threshold} {value} outside of [0, 1]", biome.name));
+                }
+            }
+            for tile_weight in &biome.tile_weights {
+                if !tile_weight.weight.is_finite() || tile_weight.weight < 0.0 {
+                    errors.push(format!("Biome {} has a negative weight for {}", biome.name, tile_weight.texture));
+                }
+                if tile_weight.patch_size.is_some_and(|patch_size| patch_size.is_nan() || patch_size < 1.0) {
+                    errors.push(format!(
+                        "Biome {} has patches of {} smaller than a cell",
+                        biome.name, tile_weight.texture
+                    ));
+                }
+            }
+            if let Some(mining) = &biome.mining {
+                if biome.kind != BiomeKind::Rock {
+                    errors.push(format!("Biome {} can be mined, but only rock can", biome.name));
+                }
+                match self.biomes.iter().find(|other| other.name == mining.mined_into) {
+                    Some(mined_into) if mined_into.kind == BiomeKind::Rock => {
+                        errors.push(format!("Biome {} is mined into {}, which is rock too", biome.name, mining.mined_into));
+                    }
+                    Some(_) => {}
+                    None => errors.push(format!("Biome {} is mined into {}, which isn't a biome", biome.name, mining.mined_into)),
+                }
+                if !mining.work.is_finite() || mining.work < 0.0 {
+                    errors.push(format!("Biome {} takes a negative time to mine", biome.name));
+                }
+                if let Some(drop) = mining.drops.iter().find(|drop| drop.amount == 0) {
+                    errors.push(format!("Biome {} drops no {}", biome.name, drop.resource));
+                }
+            }
+            if let Some(vein) = &biome.vein {
+                if !self.biomes.iter().any(|other| other.name == vein.host && other.is_placed_by_thresholds()) {
+                    errors.push(format!("Biome {} runs through {}, which isn't a biome placed by its thresholds", biome.name, vein.host));
+                }
+                if !(0.0..=1.0).contains(&vein.abundance) {
+                    errors.push(format!("Biome {} has an abundance of {} outside of [0, 1]", biome.name, vein.abundance));
+                }
+            }
+            if let Some(transitions) = &biome.transitions {
+                if !(0.0..=1.0).contains(&transitions.reach) {
+                    errors.push(format!(
+                        "Biome {} has a transition reach of {} outside of [0, 1]",
+                        biome.name, transitions.reach
+                    ));
+                }
+            }
+        }
+
+        if !self.biomes.is_empty() && !self.biomes.iter().any(Biome::is_fallback) {
+            errors.push("No biome has all its thresholds at 0, so some cells would match no biome".to_string());
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+impl Biome {
+    /// A biome with every threshold at 0 matches any cell, see `is_placed_by_thresholds`.
+    pub fn is_fallback(&self) -> bool {
+        self.is_placed_by_thresholds() && self.min_height == 0.0 && self.min_moisture == 0.0 && self.min_heat == 0.0
+    }
+
+    /// Rivers, floors and ore veins are placed some other way, their thresholds are ignored.
+    pub fn is_placed_by_thresholds(&self) -> bool {
+        !matches!(self.kind, BiomeKind::River | BiomeKind::Floor) && self.vein.is_none()
+    }
+
+    /// Whether the biome is rock, which blocks movement.
+    pub fn is_solid(&self) -> bool {
+        self.kind == BiomeKind::Rock
+    }
+
+    /// The folder the biome's tile textures come from, biomes can share another biome's tiles.
+    pub fn tile_name(&self) -> &str {
+        self.tile_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl NamedDefinition for Biome {
+    fn definition_name(&self) -> &str {
+        &self.name
+    }
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+/// The darkest the ambient overlay gets, at midnight.
+const MAX_DARKNESS: f32 = 0.75;
+
+#[derive(Resource)]
+pub struct DayCycle {
+    /// Fraction of the day that has passed, 0 is midnight and 0.5 is noon.
+    pub time_of_day: f32,
+    pub day_length_seconds: f32,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self { time_of_day: 0.3, day_length_seconds: 240.0 }
+    }
+}
+
+impl DayCycle {
+    pub fn advance(&mut self, delta_seconds: f32) {
+        self.time_of_day = (self.time_of_day + delta_seconds / self.day_length_seconds).fract();
+    }
+
+    /// Height of the sun, from -1 at midnight to 1 at noon.
+    pub fn sun_height(&self) -> f32 {
+        (TAU * (self.time_of_day - 0.25)).sin()
+    }
+
+    /// How dark the world is, from 0 during the day to 1 at full night.
+    pub fn night_factor(&self) -> f32 {
+        ((0.15 - self.sun_height()) / 0.45).clamp(0.0, 1.0)
+    }
+
+    /// The color laid over the world, warm at dusk and dawn and deep blue at night.
+    pub fn ambient_color(&self) -> Color {
+        let night_factor = self.night_factor();
+        let dusk = Srgba::new(0.45, 0.2, 0.05, 1.0);
+        let night = Srgba::new(0.02, 0.03, 0.12, 1.0);
+
+        Color::from(dusk.mix(&night, night_factor)).with_alpha(night_factor * MAX_DARKNESS)
+    }
+
+    pub fn hours_minutes(&self) -> (u32, u32) {
+        let minutes = (self.time_of_day * 24.0 * 60.0) as u32;
+        (minutes / 60, minutes % 60)
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::prelude::*;
+
+/// Problems that keep the game from going on, shown to the player instead of panicking.
+#[derive(Resource, Default)]
+pub struct GameErrors(pub Vec<String>);
@@ -0,0 +1,127 @@
+use crate::resources::biome_registry::{BiomeId, BiomeRegistry};
+use crate::resources::biomes::Biome;
+use bevy::prelude::*;
+
+/// Sent after cells of the `GameMap` changed biome or obstruction, like when they were mined out.
+#[derive(Event)]
+pub struct MapCellsChanged(pub Vec<UVec2>);
+
+/// The generated map: which biome every cell belongs to, what stands on it in the way, and the
+/// movement modifier cached from both.
+#[derive(Resource)]
+pub struct GameMap {
+    width: u32,
+    height: u32,
+    seed: u64,
+    biomes: Vec<Biome>,
+    cell_biomes: Vec<BiomeId>,
+    /// How much the things on every cell slow movement down, 1 where nothing's in the way and 0 where it's blocked.
+    obstructions: Vec<f64>,
+    movement_modifiers: Vec<f64>,
+}
+
+impl GameMap {
+    /// `biomes` are the registry's biomes the map was generated with, indexed by id.
+    pub fn new(width: u32, height: u32, seed: u64, biomes: Vec<Biome>, cell_biomes: Vec<BiomeId>) -> Self {
+        let obstructions = vec![1.0; cell_biomes.len()];
+        let mut game_map = GameMap { width, height, seed, biomes, cell_biomes, obstructions, movement_modifiers: vec![] };
+        game_map.recompute_movement_modifiers();
+        game_map
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The seed the map was generated from, anything else picked at random for the map is picked from it too.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn biomes(&self) -> &[Biome] {
+        &self.biomes
+    }
+
+    pub fn biome_id_at(&self, x: u32, y: u32) -> BiomeId {
+        self.cell_biomes[(y * self.width + x) as usize]
+    }
+
+    pub fn biome_at(&self, x: u32, y: u32) -> &Biome {
+        &self.biomes[self.biome_id_at(x, y).index()]
+    }
+
+    /// 0 where the cell can't be walked on, like on rock or behind a wall.
+    pub fn movement_modifier(&self, x: u32, y: u32) -> f64 {
+        self.movement_modifiers[(y * self.width + x) as usize]
+    }
+
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        self.biome_at(x, y).is_solid()
+    }
+
+    /// The biome the cell at `x`, `y` turns into once it's mined out, `None` when it can't be mined.
+    pub fn mined_into(&self, x: u32, y: u32) -> Option<BiomeId> {
+        let mining = self.biome_at(x, y).mining.as_ref()?;
+        let index = self.biomes.iter().position(|biome| biome.name == mining.mined_into)?;
+        Some(BiomeId(index as u16))
+    }
+
+    /// Changes the biome of a single cell, like when it's mined out.
+    pub fn set_biome(&mut self, x: u32, y: u32, biome_id: BiomeId) {
+        let index = (y * self.width + x) as usize;
+        self.cell_biomes[index] = biome_id;
+        self.movement_modifiers[index] = Self::cell_movement_modifier(&self.biomes[biome_id.index()]) * self.obstructions[index];
+    }
+
+    pub fn obstruction(&self, x: u32, y: u32) -> f64 {
+        self.obstructions[(y * self.width + x) as usize]
+    }
+
+    /// Sets how much the things on a cell slow movement down, returns whether that changed anything.
+    pub fn set_obstruction(&mut self, x: u32, y: u32, obstruction: f64) -> bool {
+        let index = (y * self.width + x) as usize;
+        if self.obstructions[index] == obstruction {
+            return false;
+        }
+        self.obstructions[index] = obstruction;
+        self.movement_modifiers[index] = Self::cell_movement_modifier(self.biome_at(x, y)) * obstruction;
+        true
+    }
+
+    /// Takes the current definitions of the map's biomes from the registry, returning the ids of
+    /// the biomes whose tiles need new textures.
+    pub fn update_biomes(&mut self, biome_registry: &BiomeRegistry) -> Vec<BiomeId> {
+        let mut retextured = vec![];
+        for (index, biome) in self.biomes.iter_mut().enumerate() {
+            let id = BiomeId(index as u16);
+            let updated = biome_registry.get(id);
+            if updated.tile_name() != biome.tile_name() || updated.tile_weights != biome.tile_weights {
+                retextured.push(id);
+            }
+            *biome = updated.clone();
+        }
+
+        self.recompute_movement_modifiers();
+        retextured
+    }
+
+    fn recompute_movement_modifiers(&mut self) {
+        self.movement_modifiers = self
+            .cell_biomes
+            .iter()
+            .zip(&self.obstructions)
+            .map(|(biome_id, obstruction)| Self::cell_movement_modifier(&self.biomes[biome_id.index()]) * obstruction)
+            .collect();
+    }
+
+    fn cell_movement_modifier(biome: &Biome) -> f64 {
+        match biome.is_solid() {
+            true => 0.0,
+            false => biome.movement_modifier,
+        }
+    }
+}
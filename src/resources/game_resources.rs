@@ -1,10 +1,42 @@
+use crate::resources::mods::NamedDefinition;
 use bevy::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Deserialize, Asset, TypePath)]
+/// Something that can be gathered, carried and stored, like wood or stone.
+#[derive(Deserialize, JsonSchema, Asset, TypePath)]
 #[serde(rename_all = "camelCase")]
 pub struct GameResource {
+    /// The name of the resource.
     pub name: String,
+    /// The weight of one unit of this resource.
+    #[schemars(range(min = 0))]
     pub weight: f32,
+    /// Group names for this resource, like vegetable or meat or cloth.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The multiplier to be applied to a villager's pickup speed, on a per-resource basis.
+    #[schemars(range(min = 0))]
     pub pickup_speed_multiplier: f32,
+    /// How much nourishment this item provides, 0 or nothing means it's inedible.
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub nourishment: f32,
+}
+
+impl NamedDefinition for GameResource {
+    fn definition_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Some units of one resource.
+#[derive(Deserialize, JsonSchema, TypePath, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceQuantity {
+    /// The name of the resource definition.
+    pub resource: String,
+    /// How many units of the resource.
+    #[schemars(range(min = 1))]
+    pub quantity: u32,
 }
@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Something the player can do with a key or mouse button. Systems react to these rather than to raw input.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum InputAction {
+    ToggleOptions,
+    PanCameraUp,
+    PanCameraDown,
+    PanCameraLeft,
+    PanCameraRight,
+    ZoomIn,
+    ZoomOut,
+    ToggleMineDesignation,
+    ToggleWorkPriorities,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 9] = [
+        InputAction::ToggleOptions,
+        InputAction::PanCameraUp,
+        InputAction::PanCameraDown,
+        InputAction::PanCameraLeft,
+        InputAction::PanCameraRight,
+        InputAction::ZoomIn,
+        InputAction::ZoomOut,
+        InputAction::ToggleMineDesignation,
+        InputAction::ToggleWorkPriorities,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::ToggleOptions => "Toggle options",
+            InputAction::PanCameraUp => "Pan camera up",
+            InputAction::PanCameraDown => "Pan camera down",
+            InputAction::PanCameraLeft => "Pan camera left",
+            InputAction::PanCameraRight => "Pan camera right",
+            InputAction::ZoomIn => "Zoom in",
+            InputAction::ZoomOut => "Zoom out",
+            InputAction::ToggleMineDesignation => "Mark rock for mining",
+            InputAction::ToggleWorkPriorities => "Toggle work priorities",
+        }
+    }
+
+    fn default_bindings(self) -> Vec<InputBinding> {
+        let keys = match self {
+            InputAction::ToggleOptions => vec![KeyCode::Escape],
+            InputAction::PanCameraUp => vec![KeyCode::KeyW, KeyCode::ArrowUp],
+            InputAction::PanCameraDown => vec![KeyCode::KeyS, KeyCode::ArrowDown],
+            InputAction::PanCameraLeft => vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            InputAction::PanCameraRight => vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            InputAction::ZoomIn => vec![KeyCode::KeyE, KeyCode::NumpadAdd],
+            InputAction::ZoomOut => vec![KeyCode::KeyQ, KeyCode::NumpadSubtract],
+            InputAction::ToggleWorkPriorities => vec![KeyCode::KeyP],
+            InputAction::ToggleMineDesignation => {
+                let shift = Modifiers { shift: true, ..default() };
+                return vec![InputBinding::mouse(MouseButton::Left).with_modifiers(shift)];
+            }
+        };
+        keys.into_iter().map(InputBinding::key).collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum BindingInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// The modifier keys that have to be held for a binding, no more and no less.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(default)]
+pub struct Modifiers {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ctrl: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub fn held(keys: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            ctrl: keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+
+    pub fn is_modifier_key(key: KeyCode) -> bool {
+        matches!(
+            key,
+            KeyCode::ControlLeft
+                | KeyCode::ControlRight
+                | KeyCode::ShiftLeft
+                | KeyCode::ShiftRight
+                | KeyCode::AltLeft
+                | KeyCode::AltRight
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InputBinding {
+    #[serde(flatten)]
+    pub input: BindingInput,
+    #[serde(flatten)]
+    pub modifiers: Modifiers,
+}
+
+impl InputBinding {
+    pub fn key(key: KeyCode) -> Self {
+        Self { input: BindingInput::Key(key), modifiers: Modifiers::default() }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self { input: BindingInput::Mouse(button), modifiers: Modifiers::default() }
+    }
+
+    pub fn with_modifiers(self, modifiers: Modifiers) -> Self {
+        Self { modifiers, ..self }
+    }
+
+    fn pressed(&self, keys: &ButtonInput<KeyCode>, mouse_buttons: &ButtonInput<MouseButton>) -> bool {
+        match self.input {
+            BindingInput::Key(key) => keys.pressed(key),
+            BindingInput::Mouse(button) => mouse_buttons.pressed(button),
+        }
+    }
+
+    fn just_pressed(&self, keys: &ButtonInput<KeyCode>, mouse_buttons: &ButtonInput<MouseButton>) -> bool {
+        match self.input {
+            BindingInput::Key(key) => keys.just_pressed(key),
+            BindingInput::Mouse(button) => mouse_buttons.just_pressed(button),
+        }
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [(self.modifiers.ctrl, "Ctrl"), (self.modifiers.shift, "Shift"), (self.modifiers.alt, "Alt")] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        match self.input {
+            BindingInput::Key(key) => write!(f, "{key:?}"),
+            BindingInput::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+/// The bindings of every action, saved with the settings.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct InputMap(BTreeMap<InputAction, Vec<InputBinding>>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self(InputAction::ALL.iter().map(|action| (*action, action.default_bindings())).collect())
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: InputAction) -> &[InputBinding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Puts `binding` in place of the binding of `action` at `slot`, or adds it after the others when `slot`
+    /// is past them, the action's other bindings stay. Any other action bound to it loses it, those actions
+    /// are returned so the player can be told.
+    pub fn bind(&mut self, action: InputAction, slot: usize, binding: InputBinding) -> Vec<InputAction> {
+        let unbound = self.take_from_others(action, &[binding]);
+        let bindings = self.0.entry(action).or_default();
+        let slot = slot.min(bindings.len());
+        match bindings.get_mut(slot) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+        // the action might have had it in another slot already
+        let duplicate = (0..bindings.len()).find(|index| *index != slot && bindings[*index] == binding);
+        if let Some(duplicate) = duplicate {
+            bindings.remove(duplicate);
+        }
+        unbound
+    }
+
+    /// Makes `bindings` the only bindings of `action`, taking them from any other action like `bind`.
+    pub fn set_bindings(&mut self, action: InputAction, bindings: Vec<InputBinding>) -> Vec<InputAction> {
+        let unbound = self.take_from_others(action, &bindings);
+        self.0.insert(action, bindings);
+        unbound
+    }
+
+    fn take_from_others(&mut self, action: InputAction, taken: &[InputBinding]) -> Vec<InputAction> {
+        let mut unbound = vec![];
+        for (other_action, bindings) in self.0.iter_mut() {
+            if *other_action != action && bindings.iter().any(|binding| taken.contains(binding)) {
+                bindings.retain(|binding| !taken.contains(binding));
+                unbound.push(*other_action);
+            }
+        }
+        unbound
+    }
+
+    /// Every binding used by more than one action, with those actions.
+    pub fn conflicts(&self) -> Vec<(InputBinding, Vec<InputAction>)> {
+        let mut conflicts: Vec<(InputBinding, Vec<InputAction>)> = vec![];
+        for (action, bindings) in self.0.iter() {
+            for binding in bindings {
+                match conflicts.iter_mut().find(|(other_binding, _)| other_binding == binding) {
+                    Some((_, actions)) => actions.push(*action),
+                    None => conflicts.push((*binding, vec![*action])),
+                }
+            }
+        }
+        conflicts.retain(|(_, actions)| actions.len() > 1);
+        conflicts
+    }
+
+    /// Gives actions added since the settings were saved their default bindings.
+    pub fn add_missing_actions(&mut self) {
+        for action in InputAction::ALL {
+            self.0.entry(action).or_insert_with(|| action.default_bindings());
+        }
+    }
+}
+
+/// Which actions are held down this frame, and which started or stopped this frame.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    just_released: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn update(&mut self, input_map: &InputMap, keys: &ButtonInput<KeyCode>, mouse_buttons: &ButtonInput<MouseButton>) {
+        let modifiers = Modifiers::held(keys);
+        let was_pressed = std::mem::take(&mut self.pressed);
+        self.just_pressed.clear();
+
+        for action in InputAction::ALL {
+            let bindings = input_map.bindings(action).iter().filter(|binding| binding.modifiers == modifiers);
+            for binding in bindings {
+                if binding.pressed(keys, mouse_buttons) {
+                    self.pressed.insert(action);
+                }
+                if binding.just_pressed(keys, mouse_buttons) {
+                    self.just_pressed.insert(action);
+                }
+            }
+        }
+
+        self.just_released = was_pressed.difference(&self.pressed).copied().collect();
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.just_released.contains(&action)
+    }
+}
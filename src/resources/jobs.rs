@@ -1,14 +1,253 @@
+use crate::resources::spatial_index::SpatialIndex;
 use bevy::prelude::*;
 
+/// The work waiting to be done. Jobs only change state through it, and every change is queued as a `JobEvent`.
 #[derive(Resource, Default)]
-pub struct Jobs(pub Vec<Job>);
+pub struct Jobs {
+    jobs: Vec<Job>,
+    next_id: u64,
+    events: Vec<JobEvent>,
+}
+
+/// Stays the same for as long as the job is around, and isn't handed out again after.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct JobId(u64);
+
+impl std::fmt::Display for JobId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
 
 pub struct Job {
+    id: JobId,
     pub name: String,
     pub job_type: JobType,
+    state: JobState,
+    /// How often the job failed in a row, the wait before the next try doubles with each.
+    failures: u32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum JobState {
+    /// Waiting for a pawn to take it.
+    Open,
+    /// Taken by a pawn that's on its way.
+    Reserved(Entity),
+    /// Being worked on by the pawn that took it.
+    InProgress(Entity),
+    /// Couldn't be done, it opens again once the game's elapsed seconds reach `retry_at`.
+    Failed { reason: String, retry_at: f64 },
+}
+
+/// Sent for every change of a job, in the order they happened.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub enum JobEvent {
+    Posted(JobId),
+    Reserved(JobId, Entity),
+    Started(JobId, Entity),
+    /// The pawn let go of the job before it was done, like when it got interrupted.
+    Released(JobId, Entity),
+    Failed { id: JobId, reason: String, retry_in: f64 },
+    Reopened(JobId),
+    Finished(JobId),
+    Cancelled(JobId),
+    /// Taken off after failing `Jobs::MAX_FAILURES` times in a row.
+    Abandoned(JobId),
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum JobType {
     PlantHarvest(Entity),
-}
\ No newline at end of file
+    /// Mining out the rock cell, see `MineDesignation`.
+    Mine(UVec2),
+}
+
+impl JobType {
+    pub fn work_type(&self) -> WorkType {
+        match self {
+            JobType::PlantHarvest(_) => WorkType::Harvest,
+            JobType::Mine(_) => WorkType::Mine,
+        }
+    }
+
+    /// Whether pawns carry the job out yet, harvests are only listed until some system does them.
+    pub fn is_carried_out(&self) -> bool {
+        matches!(self, JobType::Mine(_))
+    }
+
+    /// The cell the job is done at, `None` when what it's for isn't on the map anymore.
+    pub fn target(&self, spatial_index: &SpatialIndex) -> Option<UVec2> {
+        match self {
+            JobType::PlantHarvest(plant) => spatial_index.cell_of(*plant),
+            JobType::Mine(cell) => Some(*cell),
+        }
+    }
+}
+
+/// The kinds of work pawns can be set to do, every job is one of them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum WorkType {
+    Harvest,
+    Haul,
+    Sow,
+    Build,
+    Mine,
+}
+
+impl WorkType {
+    pub const ALL: [WorkType; 5] = [WorkType::Harvest, WorkType::Haul, WorkType::Sow, WorkType::Build, WorkType::Mine];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WorkType::Harvest => "Harvest",
+            WorkType::Haul => "Haul",
+            WorkType::Sow => "Sow",
+            WorkType::Build => "Build",
+            WorkType::Mine => "Mine",
+        }
+    }
+}
+
+impl Job {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn state(&self) -> &JobState {
+        &self.state
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The pawn that reserved the job or works on it.
+    pub fn holder(&self) -> Option<Entity> {
+        match self.state {
+            JobState::Reserved(pawn) | JobState::InProgress(pawn) => Some(pawn),
+            _ => None,
+        }
+    }
+}
+
+impl Jobs {
+    /// Seconds to wait before trying a job again after its first failure.
+    pub const RETRY_DELAY: f64 = 5.0;
+    pub const MAX_RETRY_DELAY: f64 = 120.0;
+    pub const MAX_FAILURES: u32 = 6;
+
+    pub fn post(&mut self, name: &str, job_type: JobType) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+        self.jobs.push(Job { id, name: name.to_string(), job_type, state: JobState::Open, failures: 0 });
+        self.events.push(JobEvent::Posted(id));
+        id
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    /// The jobs in the order they were posted.
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter()
+    }
+
+    pub fn find(&self, job_type: &JobType) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.job_type == *job_type)
+    }
+
+    /// Takes an open job for `pawn`, so no other pawn takes it too.
+    pub fn reserve(&mut self, id: JobId, pawn: Entity) -> Result<(), String> {
+        let job = self.job_mut(id)?;
+        if job.state != JobState::Open {
+            return Err(format!("Job {id} can't be reserved, it's {:?}", job.state));
+        }
+        job.state = JobState::Reserved(pawn);
+        self.events.push(JobEvent::Reserved(id, pawn));
+        Ok(())
+    }
+
+    /// Work started on a job `pawn` reserved. Starting a job already in progress does nothing.
+    pub fn start(&mut self, id: JobId, pawn: Entity) -> Result<(), String> {
+        let job = self.job_mut(id)?;
+        match job.state {
+            JobState::InProgress(holder) if holder == pawn => return Ok(()),
+            JobState::Reserved(holder) if holder == pawn => {}
+            _ => return Err(format!("Job {id} can't be started by {pawn}, it's {:?}", job.state)),
+        }
+        job.state = JobState::InProgress(pawn);
+        self.events.push(JobEvent::Started(id, pawn));
+        Ok(())
+    }
+
+    /// Opens a job `pawn` holds again for anyone to take.
+    pub fn release(&mut self, id: JobId, pawn: Entity) -> Result<(), String> {
+        let job = self.job_mut(id)?;
+        if job.holder() != Some(pawn) {
+            return Err(format!("Job {id} can't be released by {pawn}, it's {:?}", job.state));
+        }
+        job.state = JobState::Open;
+        self.events.push(JobEvent::Released(id, pawn));
+        Ok(())
+    }
+
+    /// Marks a job failed, to be tried again later, the wait doubling with every failure in a row. A job
+    /// that failed `MAX_FAILURES` times is given up on and taken off.
+    pub fn fail(&mut self, id: JobId, reason: &str, now: f64) -> Result<(), String> {
+        let job = self.job_mut(id)?;
+        if matches!(job.state, JobState::Failed { .. }) {
+            return Err(format!("Job {id} already failed"));
+        }
+        job.failures += 1;
+        let failures = job.failures;
+        let retry_in = (Self::RETRY_DELAY * 2f64.powi(failures as i32 - 1)).min(Self::MAX_RETRY_DELAY);
+        job.state = JobState::Failed { reason: reason.to_string(), retry_at: now + retry_in };
+        self.events.push(JobEvent::Failed { id, reason: reason.to_string(), retry_in });
+
+        if failures >= Self::MAX_FAILURES {
+            self.jobs.retain(|job| job.id != id);
+            self.events.push(JobEvent::Abandoned(id));
+        }
+        Ok(())
+    }
+
+    /// Opens the failed jobs whose wait is over.
+    pub fn reopen_due(&mut self, now: f64) {
+        for job in self.jobs.iter_mut() {
+            if matches!(job.state, JobState::Failed { retry_at, .. } if retry_at <= now) {
+                job.state = JobState::Open;
+                self.events.push(JobEvent::Reopened(job.id));
+            }
+        }
+    }
+
+    /// Takes off a job that's done.
+    pub fn finish(&mut self, id: JobId) -> Result<(), String> {
+        self.remove(id)?;
+        self.events.push(JobEvent::Finished(id));
+        Ok(())
+    }
+
+    /// Takes off a job that isn't wanted anymore.
+    pub fn cancel(&mut self, id: JobId) -> Result<(), String> {
+        self.remove(id)?;
+        self.events.push(JobEvent::Cancelled(id));
+        Ok(())
+    }
+
+    /// The events queued since the last time they were taken.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, JobEvent> {
+        self.events.drain(..)
+    }
+
+    fn remove(&mut self, id: JobId) -> Result<Job, String> {
+        let index = self.jobs.iter().position(|job| job.id == id).ok_or_else(|| format!("There's no job {id}"))?;
+        Ok(self.jobs.remove(index))
+    }
+
+    fn job_mut(&mut self, id: JobId) -> Result<&mut Job, String> {
+        self.jobs.iter_mut().find(|job| job.id == id).ok_or_else(|| format!("There's no job {id}"))
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+/// A light that shines at night.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Light {
+    /// How far the light reaches, in cells.
+    #[schemars(range(min = 1))]
+    pub range: f32,
+    pub color: LightColor,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(try_from = "Vec<f32>")]
+pub struct LightColor(pub Color);
+
+impl TryFrom<Vec<f32>> for LightColor {
+    type Error = String;
+
+    fn try_from(value: Vec<f32>) -> Result<Self, Self::Error> {
+        match value[..] {
+            [r, g, b] => Ok(LightColor(Color::srgb(r, g, b))),
+            [r, g, b, a] => Ok(LightColor(Color::srgba(r, g, b, a))),
+            _ => Err(format!("expected 3 or 4 color components, got {}", value.len())),
+        }
+    }
+}
+
+impl JsonSchema for LightColor {
+    fn schema_name() -> String {
+        "Color".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        serde_json::from_value(json!({
+            "description": "An sRGB color as [red, green, blue] or [red, green, blue, alpha], each from 0 to 1.",
+            "type": "array",
+            "minItems": 3,
+            "maxItems": 4,
+            "items": { "type": "number", "minimum": 0, "maximum": 1 }
+        }))
+        .expect("the color schema is valid")
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The folder under the asset root that holds one folder per mod.
+pub const MODS_FOLDER: &str = "mods";
+
+/// The `mod.json` at the root of every folder under `mods/`.
+#[derive(Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModManifest {
+    /// The unique id of the mod, other mods list it in their dependencies.
+    pub id: String,
+    /// The name shown to the player, defaults to the id.
+    pub name: Option<String>,
+    /// The version of the mod.
+    pub version: String,
+    /// Mods with a lower load order load first, later mods override the definitions of earlier ones.
+    #[serde(default)]
+    pub load_order: i32,
+    /// The ids of the mods that have to be loaded before this one.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl ModManifest {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+pub struct ActiveMod {
+    pub manifest: ModManifest,
+    /// The mod's folder, relative to the asset root.
+    pub folder: PathBuf,
+}
+
+/// The mods layered over the base game, in load order, and the ones that couldn't be used.
+#[derive(Resource, Default)]
+pub struct ActiveMods {
+    pub mods: Vec<ActiveMod>,
+    /// The folder of every mod that was left out, with the reason.
+    pub disabled: Vec<(PathBuf, String)>,
+}
+
+impl ActiveMods {
+    /// Orders the mods found under `mods/` by load order then id, so that every mod loads after its
+    /// dependencies. Mods with a broken manifest, a taken id or dependencies that can't be loaded are disabled.
+    pub fn resolve(found: Vec<(PathBuf, Result<ModManifest, String>)>) -> Self {
+        let mut active_mods = ActiveMods::default();
+
+        let mut pending: Vec<ActiveMod> = vec![];
+        for (folder, manifest) in found {
+            match manifest {
+                Ok(manifest) if pending.iter().any(|other| other.manifest.id == manifest.id) => {
+                    let reason = format!("Another mod already uses the id {}", manifest.id);
+                    active_mods.disabled.push((folder, reason));
+                }
+                Ok(manifest) => pending.push(ActiveMod { manifest, folder }),
+                Err(error) => active_mods.disabled.push((folder, error)),
+            }
+        }
+        pending.sort_by(|a, b| {
+            (a.manifest.load_order, &a.manifest.id).cmp(&(b.manifest.load_order, &b.manifest.id))
+        });
+        let installed: Vec<String> = pending.iter().map(|active_mod| active_mod.manifest.id.clone()).collect();
+
+        // take the first mod in load order whose dependencies are all in, until none is left that can be
+        while let Some(index) = pending.iter().position(|candidate| {
+            candidate
+                .manifest
+                .dependencies
+                .iter()
+                .all(|dependency| active_mods.is_active(dependency))
+        }) {
+            active_mods.mods.push(pending.remove(index));
+        }
+
+        for active_mod in pending {
+            let reasons: Vec<_> = active_mod
+                .manifest
+                .dependencies
+                .iter()
+                .filter(|dependency| !active_mods.is_active(dependency))
+                .map(|dependency| match installed.contains(dependency) {
+                    true => format!("Requires {dependency}, which could not be loaded"),
+                    false => format!("Requires {dependency}, which is not installed"),
+                })
+                .collect();
+            active_mods.disabled.push((active_mod.folder, reasons.join(", ")));
+        }
+
+        active_mods
+    }
+
+    pub fn is_active(&self, id: &str) -> bool {
+        self.mods.iter().any(|active_mod| active_mod.manifest.id == id)
+    }
+}
+
+/// Definitions that mods can override by giving theirs the same name.
+pub trait NamedDefinition {
+    fn definition_name(&self) -> &str;
+}
+
+/// The folder of the mod a file under the asset root belongs to, `None` for the base game's files.
+pub fn mod_folder_of(path: &Path) -> Option<PathBuf> {
+    let mut components = path.iter();
+    if components.next()? != MODS_FOLDER {
+        return None;
+    }
+    Some(Path::new(MODS_FOLDER).join(components.next()?))
+}
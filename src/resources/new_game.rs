@@ -0,0 +1,62 @@
+use crate::resources::biome_registry::BiomeRegistry;
+use crate::systems::map_generation::MapParameters;
+use crate::systems::rivers::RiverParameters;
+use bevy::prelude::*;
+
+/// What the player picked on the new game screen, map generation and world setup read it.
+#[derive(Resource, Clone, Debug)]
+pub struct NewGameSettings {
+    pub width: u32,
+    pub height: u32,
+    pub seed: u64,
+    pub pawn_count: u32,
+    pub river_count: u32,
+    /// How common each biome is, by name: 1 leaves the biome table as it is, 0 only uses the biome
+    /// where nothing else fits. Biomes that aren't listed have a weight of 1.
+    pub biome_weights: Vec<(String, f64)>,
+}
+
+impl Default for NewGameSettings {
+    fn default() -> Self {
+        Self {
+            width: 300,
+            height: 300,
+            seed: rand::random(),
+            pawn_count: 3,
+            river_count: 8,
+            biome_weights: vec![],
+        }
+    }
+}
+
+impl NewGameSettings {
+    pub const MIN_MAP_SIZE: u32 = 50;
+    pub const MAX_MAP_SIZE: u32 = 1000;
+    pub const MAX_PAWN_COUNT: u32 = 20;
+    pub const MAX_RIVER_COUNT: u32 = 50;
+    pub const MAX_BIOME_WEIGHT: f64 = 3.0;
+
+    pub fn biome_weight(&self, name: &str) -> f64 {
+        self.biome_weights
+            .iter()
+            .find(|(biome_name, _)| biome_name == name)
+            .map_or(1.0, |(_, weight)| *weight)
+    }
+
+    pub fn set_biome_weight(&mut self, name: &str, weight: f64) {
+        match self.biome_weights.iter_mut().find(|(biome_name, _)| biome_name == name) {
+            Some((_, existing)) => *existing = weight,
+            None => self.biome_weights.push((name.to_string(), weight)),
+        }
+    }
+
+    pub fn map_parameters(&self, biome_registry: &BiomeRegistry) -> MapParameters {
+        MapParameters {
+            width: self.width,
+            height: self.height,
+            seed: self.seed,
+            biome_weights: biome_registry.biomes().iter().map(|biome| self.biome_weight(&biome.name)).collect(),
+            rivers: RiverParameters { count: self.river_count, ..default() },
+        }
+    }
+}
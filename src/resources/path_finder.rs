@@ -0,0 +1,571 @@
+use crate::resources::game_map::GameMap;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::OnceLock;
+
+/// Entrances between clusters longer than this get a transition at both ends instead of one in the middle.
+const LONG_ENTRANCE: usize = 6;
+
+const NEIGHBORS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A way across the map, from its first cell to its last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    pub cells: Vec<UVec2>,
+    /// The sum of the steps' costs, a straight step over a cell with a movement modifier of 1 costs 1.
+    pub cost: f32,
+}
+
+/// Finds paths over the map for every pawn, quick enough to run for every job search on large maps.
+///
+/// The map is split into square clusters. Where two clusters meet, the cells paths can cross on are
+/// transitions, and the costs between the transitions of a cluster are worked out ahead. A path search
+/// first runs over the transitions only, then fills in the cells of the clusters along the way. Changing
+/// cells only works the clusters around them out again.
+#[derive(Resource)]
+pub struct PathFinder {
+    grid: CostGrid,
+    /// How many clusters the map is wide and high.
+    clusters_size: UVec2,
+    clusters: Vec<Cluster>,
+    /// The pairs of cells paths cross between two neighboring clusters on, by the clusters' indices, lowest first.
+    borders: HashMap<(usize, usize), Vec<(UVec2, UVec2)>>,
+    /// Every transition's neighbors across a border, with the cost of stepping over.
+    crossings: HashMap<UVec2, Vec<(UVec2, f32)>>,
+    /// Which part of the map every region of every cluster belongs to, regions of the same part reach each other.
+    /// Joined up again on the first question about it after cells changed.
+    components: OnceLock<Vec<Vec<u32>>>,
+}
+
+struct Cluster {
+    bounds: Bounds,
+    /// The region of every cell within the bounds, cells of a region reach each other without leaving
+    /// the cluster. `None` for the cells that can't be walked on.
+    regions: Vec<Option<u32>>,
+    region_count: u32,
+    /// The transitions' costs to the other transitions of the cluster they reach, by the transition's cell.
+    edges: HashMap<UVec2, Vec<(UVec2, f32)>>,
+    /// How many times the regions or edges were worked out.
+    revision: u32,
+}
+
+impl PathFinder {
+    /// The width and height of a cluster, in cells.
+    pub const CLUSTER_SIZE: u32 = 16;
+
+    pub fn new(game_map: &GameMap) -> Self {
+        let grid = CostGrid::new(game_map);
+        let clusters_size = (grid.size + Self::CLUSTER_SIZE - 1) / Self::CLUSTER_SIZE;
+        let clusters = (0..clusters_size.y)
+            .flat_map(|y| (0..clusters_size.x).map(move |x| UVec2::new(x, y)))
+            .map(|cluster| {
+                let min = cluster * Self::CLUSTER_SIZE;
+                let bounds = Bounds { min, max: (min + Self::CLUSTER_SIZE).min(grid.size) };
+                Cluster { bounds, regions: vec![], region_count: 0, edges: HashMap::new(), revision: 0 }
+            })
+            .collect();
+
+        let mut path_finder = Self {
+            grid,
+            clusters_size,
+            clusters,
+            borders: HashMap::new(),
+            crossings: HashMap::new(),
+            components: OnceLock::new(),
+        };
+        let all: Vec<_> = (0..path_finder.clusters.len()).collect();
+        path_finder.rebuild(&all);
+        path_finder
+    }
+
+    /// Takes the current movement modifiers of `cells` from the map, like after they were mined out.
+    pub fn update_cells(&mut self, game_map: &GameMap, cells: &[UVec2]) {
+        let mut dirty = HashSet::new();
+        for cell in cells {
+            self.grid.set_cost(*cell, cell_cost(game_map.movement_modifier(cell.x, cell.y)));
+            // a cell decides whether its neighbors can step diagonally past it, and they can be in other clusters
+            for (dx, dy) in NEIGHBORS.iter().chain([(0, 0)].iter()) {
+                if let Some(neighbor) = self.grid.inside(cell.as_ivec2() + IVec2::new(*dx, *dy), &self.grid.bounds()) {
+                    dirty.insert(self.cluster_index(neighbor));
+                }
+            }
+        }
+        let mut dirty: Vec<_> = dirty.into_iter().collect();
+        dirty.sort();
+        self.rebuild(&dirty);
+    }
+
+    /// Whether a pawn at `from` can walk to `to`, without searching for the path.
+    pub fn is_reachable(&self, from: UVec2, to: UVec2) -> bool {
+        match (self.component(from), self.component(to)) {
+            (Some(from), Some(to)) => from == to,
+            _ => false,
+        }
+    }
+
+    /// Whether a pawn at `from` can walk up next to `cell`, like to dig at rock it can't stand on.
+    pub fn is_reachable_next_to(&self, from: UVec2, cell: UVec2) -> bool {
+        NEIGHBORS.iter().any(|(dx, dy)| {
+            self.grid
+                .inside(cell.as_ivec2() + IVec2::new(*dx, *dy), &self.grid.bounds())
+                .is_some_and(|neighbor| self.is_reachable(from, neighbor))
+        })
+    }
+
+    /// A short path from `from` to `to`, `None` when `to` can't be reached. The path is close to the
+    /// shortest one, but can take a detour over the transitions between clusters.
+    pub fn find_path(&self, from: UVec2, to: UVec2) -> Option<Path> {
+        if !self.is_reachable(from, to) {
+            return None;
+        }
+        let (start_index, goal_index) = (self.cluster_index(from), self.cluster_index(to));
+        let (start_cluster, goal_cluster) = (&self.clusters[start_index], &self.clusters[goal_index]);
+        if start_index == goal_index && start_cluster.region(from) == start_cluster.region(to) {
+            return a_star(&self.grid, from, to, &start_cluster.bounds);
+        }
+
+        // the start and the goal join the transitions of their clusters for the search
+        let start_edges = start_cluster.costs_to_transitions(&self.grid, from);
+        let goal_edges: HashMap<UVec2, f32> = goal_cluster.costs_to_transitions(&self.grid, to).into_iter().collect();
+
+        let mut costs: HashMap<UVec2, f32> = HashMap::new();
+        let mut came_from: HashMap<UVec2, UVec2> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(from, 0.0);
+        open.push(Open { estimate: self.grid.estimate(from, to), cost: 0.0, cell: from });
+
+        while let Some(Open { cost, cell, .. }) = open.pop() {
+            if cell == to {
+                break;
+            }
+            if costs.get(&cell).is_some_and(|best| cost > *best) {
+                continue;
+            }
+
+            let within_cluster = match cell == from {
+                true => start_edges.as_slice(),
+                false => self.clusters[self.cluster_index(cell)].edges.get(&cell).map_or(&[][..], Vec::as_slice),
+            };
+            let crossing = self.crossings.get(&cell).map_or(&[][..], Vec::as_slice);
+            let to_goal = goal_edges.get(&cell).map(|cost| (to, *cost));
+            for (next, step) in within_cluster.iter().chain(crossing).copied().chain(to_goal) {
+                let next_cost = cost + step;
+                if costs.get(&next).is_some_and(|best| next_cost >= *best) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Open { estimate: next_cost + self.grid.estimate(next, to), cost: next_cost, cell: next });
+            }
+        }
+
+        let mut waypoints = vec![to];
+        while let Some(previous) = came_from.get(waypoints.last()?) {
+            waypoints.push(*previous);
+        }
+        waypoints.reverse();
+
+        // fill in the cells between the waypoints, they're either neighbors across a border or in the same cluster
+        let mut path = Path { cells: vec![from], cost: 0.0 };
+        for pair in waypoints.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if self.cluster_index(a) != self.cluster_index(b) {
+                path.cells.push(b);
+                path.cost += self.grid.step_cost(a, b);
+                continue;
+            }
+            let part = a_star(&self.grid, a, b, &self.clusters[self.cluster_index(a)].bounds)?;
+            path.cells.extend(&part.cells[1..]);
+            path.cost += part.cost;
+        }
+        Some(path)
+    }
+
+    /// The shortest path from `from` to `to` by a plain A* over every cell, the hierarchical search is measured against it.
+    pub fn find_path_flat(&self, from: UVec2, to: UVec2) -> Option<Path> {
+        a_star(&self.grid, from, to, &self.grid.bounds())
+    }
+
+    fn cluster_index(&self, cell: UVec2) -> usize {
+        let cluster = cell / Self::CLUSTER_SIZE;
+        (cluster.y * self.clusters_size.x + cluster.x) as usize
+    }
+
+    fn component(&self, cell: UVec2) -> Option<u32> {
+        if cell.cmpge(self.grid.size).any() {
+            return None;
+        }
+        let cluster_index = self.cluster_index(cell);
+        let region = self.clusters[cluster_index].region(cell)?;
+        Some(self.components.get_or_init(|| self.connect_regions())[cluster_index][region as usize])
+    }
+
+    /// How many times the cluster `cell` is in was worked out, changing cells elsewhere leaves it be.
+    pub fn cluster_revision(&self, cell: UVec2) -> u32 {
+        self.clusters[self.cluster_index(cell)].revision
+    }
+
+    /// Works out `dirty` clusters and their borders again, along with the transitions of the neighbors whose
+    /// shared borders changed.
+    fn rebuild(&mut self, dirty: &[usize]) {
+        for cluster_index in dirty {
+            let cluster = &mut self.clusters[*cluster_index];
+            (cluster.regions, cluster.region_count) = regions(&self.grid, &cluster.bounds);
+        }
+
+        let mut touched: Vec<usize> = dirty.to_vec();
+        for cluster_index in dirty {
+            for neighbor in self.neighbor_clusters(*cluster_index) {
+                let key = (*cluster_index.min(&neighbor), *cluster_index.max(&neighbor));
+                let crossings = border_crossings(
+                    &self.grid,
+                    &self.clusters[key.0].bounds,
+                    &self.clusters[key.1].bounds,
+                );
+                for (a, b) in crossings.iter().copied() {
+                    self.remove_crossing(a, b);
+                    self.remove_crossing(b, a);
+                    let step = self.grid.step_cost(a, b);
+                    self.crossings.entry(a).or_default().push((b, step));
+                    self.crossings.entry(b).or_default().push((a, step));
+                }
+                let previous = self.borders.insert(key, crossings.clone()).unwrap_or_default();
+                if previous == crossings {
+                    continue;
+                }
+                for (a, b) in previous.into_iter().filter(|pair| !crossings.contains(pair)) {
+                    self.remove_crossing(a, b);
+                    self.remove_crossing(b, a);
+                }
+                // the neighbor's transitions on the border moved, so do its costs between them
+                touched.push(neighbor);
+            }
+        }
+        touched.sort();
+        touched.dedup();
+
+        for cluster_index in touched {
+            let transitions: Vec<UVec2> = self
+                .neighbor_clusters(cluster_index)
+                .flat_map(|neighbor| {
+                    let key = (cluster_index.min(neighbor), cluster_index.max(neighbor));
+                    self.borders.get(&key).into_iter().flatten().map(move |(a, b)| match key.0 == cluster_index {
+                        true => *a,
+                        false => *b,
+                    })
+                })
+                .collect();
+            let cluster = &self.clusters[cluster_index];
+            let edges = transitions
+                .iter()
+                .map(|transition| (*transition, cluster.costs_to(&self.grid, *transition, &transitions)))
+                .collect();
+            let cluster = &mut self.clusters[cluster_index];
+            cluster.edges = edges;
+            cluster.revision += 1;
+        }
+        self.components = OnceLock::new();
+    }
+
+    fn remove_crossing(&mut self, from: UVec2, to: UVec2) {
+        if let Some(neighbors) = self.crossings.get_mut(&from) {
+            neighbors.retain(|(neighbor, _)| *neighbor != to);
+            if neighbors.is_empty() {
+                self.crossings.remove(&from);
+            }
+        }
+    }
+
+    /// The clusters east, west, north and south of the cluster.
+    fn neighbor_clusters(&self, cluster_index: usize) -> impl Iterator<Item = usize> {
+        let size = self.clusters_size.as_ivec2();
+        let cluster = IVec2::new(cluster_index as i32 % size.x, cluster_index as i32 / size.x);
+        [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+            .into_iter()
+            .map(move |offset| cluster + offset)
+            .filter(move |neighbor| neighbor.cmpge(IVec2::ZERO).all() && neighbor.cmplt(size).all())
+            .map(move |neighbor| (neighbor.y * size.x + neighbor.x) as usize)
+    }
+
+    /// Joins the regions of neighboring clusters that paths cross between.
+    fn connect_regions(&self) -> Vec<Vec<u32>> {
+        let mut offsets = Vec::with_capacity(self.clusters.len());
+        let mut total = 0;
+        for cluster in &self.clusters {
+            offsets.push(total);
+            total += cluster.region_count;
+        }
+        let key = |cell: UVec2| {
+            let cluster_index = self.cluster_index(cell);
+            offsets[cluster_index] + self.clusters[cluster_index].region(cell).unwrap()
+        };
+
+        let mut parents: Vec<u32> = (0..total).collect();
+        fn root(parents: &mut [u32], mut key: u32) -> u32 {
+            while parents[key as usize] != key {
+                parents[key as usize] = parents[parents[key as usize] as usize];
+                key = parents[key as usize];
+            }
+            key
+        }
+        for (a, b) in self.borders.values().flatten() {
+            let (a, b) = (root(&mut parents, key(*a)), root(&mut parents, key(*b)));
+            parents[a.max(b) as usize] = a.min(b);
+        }
+
+        self.clusters
+            .iter()
+            .zip(offsets)
+            .map(|(cluster, offset)| (0..cluster.region_count).map(|region| root(&mut parents, offset + region)).collect())
+            .collect()
+    }
+}
+
+impl Cluster {
+    fn region(&self, cell: UVec2) -> Option<u32> {
+        self.regions[self.bounds.local(cell)]
+    }
+
+    /// The costs from `cell` to every transition of the cluster it reaches without leaving the cluster.
+    fn costs_to_transitions(&self, grid: &CostGrid, cell: UVec2) -> Vec<(UVec2, f32)> {
+        let transitions: Vec<_> = self.edges.keys().copied().collect();
+        self.costs_to(grid, cell, &transitions)
+    }
+
+    fn costs_to(&self, grid: &CostGrid, cell: UVec2, targets: &[UVec2]) -> Vec<(UVec2, f32)> {
+        let costs = search(grid, cell, None, &self.bounds).0;
+        targets
+            .iter()
+            .filter(|target| **target != cell)
+            .map(|target| (*target, costs[self.bounds.local(*target)]))
+            .filter(|(_, cost)| cost.is_finite())
+            .collect()
+    }
+}
+
+/// The cells within a pair of neighboring clusters' bounds that paths cross between them on, the first cell
+/// of each pair in `a`. Every stretch of the border that can be crossed gets a crossing in its middle, long
+/// ones one at each end.
+fn border_crossings(grid: &CostGrid, a: &Bounds, b: &Bounds) -> Vec<(UVec2, UVec2)> {
+    let pairs: Vec<(UVec2, UVec2)> = if a.max.x == b.min.x {
+        (a.min.y..a.max.y).map(|y| (UVec2::new(a.max.x - 1, y), UVec2::new(b.min.x, y))).collect()
+    } else {
+        (a.min.x..a.max.x).map(|x| (UVec2::new(x, a.max.y - 1), UVec2::new(x, b.min.y))).collect()
+    };
+
+    let mut crossings = vec![];
+    let mut stretch: Vec<(UVec2, UVec2)> = vec![];
+    for pair in pairs.into_iter().map(Some).chain([None]) {
+        if let Some((cell_a, cell_b)) = pair.filter(|(cell_a, cell_b)| grid.is_passable(*cell_a) && grid.is_passable(*cell_b)) {
+            stretch.push((cell_a, cell_b));
+            continue;
+        }
+        match stretch.len() {
+            0 => {}
+            length if length > LONG_ENTRANCE => crossings.extend([stretch[0], stretch[length - 1]]),
+            length => crossings.push(stretch[length / 2]),
+        }
+        stretch.clear();
+    }
+    crossings
+}
+
+/// Splits the cells within `bounds` into the parts that reach each other without leaving them.
+fn regions(grid: &CostGrid, bounds: &Bounds) -> (Vec<Option<u32>>, u32) {
+    let size = bounds.size();
+    let mut regions = vec![None; (size.x * size.y) as usize];
+    let mut count = 0;
+    for y in bounds.min.y..bounds.max.y {
+        for x in bounds.min.x..bounds.max.x {
+            let cell = UVec2::new(x, y);
+            if regions[bounds.local(cell)].is_some() || !grid.is_passable(cell) {
+                continue;
+            }
+            regions[bounds.local(cell)] = Some(count);
+            let mut stack = vec![cell];
+            while let Some(current) = stack.pop() {
+                for (next, _) in grid.steps(current, bounds) {
+                    if regions[bounds.local(next)].is_none() {
+                        regions[bounds.local(next)] = Some(count);
+                        stack.push(next);
+                    }
+                }
+            }
+            count += 1;
+        }
+    }
+    (regions, count)
+}
+
+/// The cheapest path from `start` to `goal` through the cells within `bounds`.
+fn a_star(grid: &CostGrid, start: UVec2, goal: UVec2, bounds: &Bounds) -> Option<Path> {
+    let (costs, came_from) = search(grid, start, Some(goal), bounds);
+    let cost = costs[bounds.local(goal)];
+    if !cost.is_finite() {
+        return None;
+    }
+
+    let mut cells = vec![goal];
+    while let Some(previous) = came_from[bounds.local(*cells.last()?)] {
+        cells.push(previous);
+    }
+    cells.reverse();
+    Some(Path { cells, cost })
+}
+
+/// The costs of reaching the cells within `bounds` from `start`, by their index within the bounds, along
+/// with the cell each was reached from. Stops once it reached `goal`, when there's one to head for.
+fn search(grid: &CostGrid, start: UVec2, goal: Option<UVec2>, bounds: &Bounds) -> (Vec<f32>, Vec<Option<UVec2>>) {
+    let size = bounds.size();
+    let mut costs = vec![f32::INFINITY; (size.x * size.y) as usize];
+    let mut came_from = vec![None; costs.len()];
+    let mut open = BinaryHeap::new();
+    let estimate = |cell: UVec2| goal.map_or(0.0, |goal| grid.estimate(cell, goal));
+
+    costs[bounds.local(start)] = 0.0;
+    open.push(Open { estimate: estimate(start), cost: 0.0, cell: start });
+    while let Some(Open { cost, cell, .. }) = open.pop() {
+        if Some(cell) == goal {
+            break;
+        }
+        if cost > costs[bounds.local(cell)] {
+            continue;
+        }
+        for (next, step) in grid.steps(cell, bounds) {
+            let next_cost = cost + step;
+            let local = bounds.local(next);
+            if next_cost >= costs[local] {
+                continue;
+            }
+            costs[local] = next_cost;
+            came_from[local] = Some(cell);
+            open.push(Open { estimate: next_cost + estimate(next), cost: next_cost, cell: next });
+        }
+    }
+    (costs, came_from)
+}
+
+/// What stepping onto a cell costs for every cell of the map, infinite for the ones that can't be walked on.
+struct CostGrid {
+    size: UVec2,
+    costs: Vec<f32>,
+    /// The lowest cost of any cell, so the estimates never overshoot.
+    min_cost: f32,
+}
+
+impl CostGrid {
+    fn new(game_map: &GameMap) -> Self {
+        let size = UVec2::new(game_map.width(), game_map.height());
+        let costs: Vec<f32> = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| cell_cost(game_map.movement_modifier(x, y)))
+            .collect();
+        let min_cost = costs.iter().copied().fold(1.0, f32::min);
+        Self { size, costs, min_cost }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds { min: UVec2::ZERO, max: self.size }
+    }
+
+    fn set_cost(&mut self, cell: UVec2, cost: f32) {
+        self.costs[(cell.y * self.size.x + cell.x) as usize] = cost;
+        self.min_cost = self.min_cost.min(cost);
+    }
+
+    fn is_passable(&self, cell: UVec2) -> bool {
+        self.costs[(cell.y * self.size.x + cell.x) as usize].is_finite()
+    }
+
+    /// Steps are charged the average of the two cells' costs, so a path costs the same both ways.
+    fn step_cost(&self, a: UVec2, b: UVec2) -> f32 {
+        let length = if a.x != b.x && a.y != b.y { std::f32::consts::SQRT_2 } else { 1.0 };
+        let cost = |cell: UVec2| self.costs[(cell.y * self.size.x + cell.x) as usize];
+        length * (cost(a) + cost(b)) / 2.0
+    }
+
+    /// The cheapest a path between the cells could possibly be.
+    fn estimate(&self, a: UVec2, b: UVec2) -> f32 {
+        let distance = (a.as_vec2() - b.as_vec2()).abs();
+        let (long, short) = (distance.max_element(), distance.min_element());
+        (long - short + short * std::f32::consts::SQRT_2) * self.min_cost
+    }
+
+    fn inside(&self, cell: IVec2, bounds: &Bounds) -> Option<UVec2> {
+        let inside = cell.cmpge(bounds.min.as_ivec2()).all() && cell.cmplt(bounds.max.as_ivec2()).all();
+        inside.then(|| cell.as_uvec2())
+    }
+
+    /// The cells within `bounds` a pawn on `cell` can step to, with the steps' costs. Diagonal steps can't
+    /// cut the corner of a cell that can't be walked on.
+    fn steps<'a>(&'a self, cell: UVec2, bounds: &'a Bounds) -> impl Iterator<Item = (UVec2, f32)> + 'a {
+        NEIGHBORS.iter().filter_map(move |(dx, dy)| {
+            let next = self.inside(cell.as_ivec2() + IVec2::new(*dx, *dy), bounds)?;
+            if !self.is_passable(next) {
+                return None;
+            }
+            let diagonal = *dx != 0 && *dy != 0;
+            if diagonal && !(self.is_passable(UVec2::new(next.x, cell.y)) && self.is_passable(UVec2::new(cell.x, next.y))) {
+                return None;
+            }
+            Some((next, self.step_cost(cell, next)))
+        })
+    }
+}
+
+fn cell_cost(movement_modifier: f64) -> f32 {
+    match movement_modifier > 0.0 {
+        true => (1.0 / movement_modifier) as f32,
+        false => f32::INFINITY,
+    }
+}
+
+/// A rectangle of cells, `max` is just past its last cell.
+struct Bounds {
+    min: UVec2,
+    max: UVec2,
+}
+
+impl Bounds {
+    fn size(&self) -> UVec2 {
+        self.max - self.min
+    }
+
+    /// The cell's index within the bounds, row by row.
+    fn local(&self, cell: UVec2) -> usize {
+        let offset = cell - self.min;
+        (offset.y * self.size().x + offset.x) as usize
+    }
+}
+
+/// A cell waiting to be searched from, the heap hands out the lowest estimate first.
+struct Open {
+    estimate: f32,
+    cost: f32,
+    cell: UVec2,
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate
+            .total_cmp(&self.estimate)
+            .then(self.cost.total_cmp(&other.cost))
+            .then((other.cell.y, other.cell.x).cmp(&(self.cell.y, self.cell.x)))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
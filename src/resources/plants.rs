@@ -0,0 +1,31 @@
+use crate::resources::light::Light;
+use crate::resources::mods::NamedDefinition;
+use bevy::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// A kind of plant that can be spawned into the world.
+#[derive(Deserialize, JsonSchema, Asset, TypePath, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlantDefinition {
+    /// The name of the plant, matching its texture in the plants folder.
+    pub name: String,
+    /// The light this plant gives off at night.
+    pub light: Option<Light>,
+    /// How much the plant slows down pawns walking through its cell, 0 blocks them. Defaults to 1, not at all.
+    #[serde(default = "PlantDefinition::default_movement_modifier")]
+    #[schemars(range(min = 0, max = 1))]
+    pub movement_modifier: f64,
+}
+
+impl PlantDefinition {
+    fn default_movement_modifier() -> f64 {
+        1.0
+    }
+}
+
+impl NamedDefinition for PlantDefinition {
+    fn definition_name(&self) -> &str {
+        &self.name
+    }
+}
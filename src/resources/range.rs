@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// A span of values for data files, from `min` to `max`.
+#[derive(Deserialize, JsonSchema, TypePath, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Range {
+    /// The lowest value, included.
+    pub min: f64,
+    /// The highest value, included.
+    pub max: f64,
+}
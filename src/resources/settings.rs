@@ -0,0 +1,138 @@
+use crate::resources::input_actions::InputMap;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The player's settings, kept in `settings.json` in the user's config directory.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub window_mode: WindowModeSetting,
+    /// The window size, also the resolution used in fullscreen.
+    pub resolution: Resolution,
+    pub vsync: bool,
+    /// The most frames per second to render, `None` for no limit.
+    pub frame_cap: Option<u32>,
+    pub ui_scale: f32,
+    pub keybindings: InputMap,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowModeSetting {
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::BorderlessFullscreen,
+            resolution: Resolution { width: 1920, height: 1080 },
+            vsync: true,
+            frame_cap: Some(60),
+            ui_scale: 1.0,
+            keybindings: InputMap::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub const MIN_UI_SCALE: f32 = 0.5;
+    pub const MAX_UI_SCALE: f32 = 3.0;
+
+    /// Where the settings are saved, `None` when the platform has no config directory.
+    pub fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("meadowland").join("settings.json"))
+    }
+
+    /// Loads the settings from the user's config directory, see `load_or_regenerate`.
+    pub fn load() -> Self {
+        match Self::path() {
+            Some(path) => Self::load_or_regenerate(&path),
+            None => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("There is no config directory to save the settings in")?;
+        self.save_to(&path)
+    }
+
+    /// Loads the settings at `path`. When the file is missing or can't be read, it's written again with
+    /// the default settings, a corrupt file is kept next to it as `settings.json.bak`.
+    pub fn load_or_regenerate(path: &Path) -> Self {
+        // this runs before the app exists, so there's no logging yet
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Settings>(&json) {
+                Ok(settings) => return settings.repaired(),
+                Err(error) => {
+                    eprintln!("{} is corrupt, using the default settings: {error}", path.display());
+                    let _ = fs::rename(path, path.with_extension("json.bak"));
+                }
+            },
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => eprintln!("Could not read {}, using the default settings: {error}", path.display()),
+        }
+
+        let settings = Self::default();
+        if let Err(error) = settings.save_to(path) {
+            eprintln!("{error}");
+        }
+        settings
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder).map_err(|error| format!("Could not create {}: {error}", folder.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| format!("Could not save {}: {error}", path.display()))
+    }
+
+    /// Brings hand-edited values back into a usable range, and binds actions that are new since the file was saved.
+    fn repaired(mut self) -> Self {
+        self.ui_scale = self.ui_scale.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE);
+        self.resolution.width = self.resolution.width.max(640);
+        self.resolution.height = self.resolution.height.max(480);
+        self.frame_cap = self.frame_cap.filter(|frame_cap| *frame_cap > 0);
+        self.keybindings.add_missing_actions();
+        self
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self.window_mode {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            // picks the monitor's video mode closest to the resolution
+            WindowModeSetting::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        match self.vsync {
+            true => PresentMode::AutoVsync,
+            false => PresentMode::AutoNoVsync,
+        }
+    }
+
+    pub fn window_resolution(&self) -> WindowResolution {
+        WindowResolution::new(self.resolution.width as f32, self.resolution.height as f32)
+    }
+
+    pub fn frame_limiter(&self) -> bevy_framepace::Limiter {
+        match self.frame_cap {
+            Some(frame_cap) => bevy_framepace::Limiter::from_framerate(frame_cap as f64),
+            None => bevy_framepace::Limiter::Off,
+        }
+    }
+}
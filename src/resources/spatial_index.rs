@@ -0,0 +1,139 @@
+use crate::components::tile_occupant::TileOccupant;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// The map cells of the entities on the map, to find what's on a cell or near it without going over every entity.
+///
+/// Entries are kept in square buckets of cells, a query only looks into the buckets it overlaps.
+#[derive(Resource, Default)]
+pub struct SpatialIndex {
+    buckets: HashMap<UVec2, Vec<Entry>>,
+    cells: HashMap<Entity, (UVec2, TileOccupant)>,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    entity: Entity,
+    cell: UVec2,
+    occupant: TileOccupant,
+}
+
+impl SpatialIndex {
+    /// The width and height of a bucket, in cells.
+    const BUCKET_SIZE: u32 = 8;
+
+    /// Puts `entity` on `cell`, taking it off the cell it was on before.
+    pub fn insert(&mut self, entity: Entity, cell: UVec2, occupant: TileOccupant) {
+        if self.cells.get(&entity) == Some(&(cell, occupant)) {
+            return;
+        }
+        self.remove(entity);
+        self.cells.insert(entity, (cell, occupant));
+        self.buckets.entry(cell / Self::BUCKET_SIZE).or_default().push(Entry { entity, cell, occupant });
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some((cell, _)) = self.cells.remove(&entity) else {
+            return;
+        };
+        let bucket = cell / Self::BUCKET_SIZE;
+        if let Some(entries) = self.buckets.get_mut(&bucket) {
+            entries.retain(|entry| entry.entity != entity);
+            if entries.is_empty() {
+                self.buckets.remove(&bucket);
+            }
+        }
+    }
+
+    /// The cell `entity` is on, `None` when it isn't indexed.
+    pub fn cell_of(&self, entity: Entity) -> Option<UVec2> {
+        self.cells.get(&entity).map(|(cell, _)| *cell)
+    }
+
+    /// The entities of a kind on `cell`.
+    pub fn at(&self, cell: UVec2, occupant: TileOccupant) -> impl Iterator<Item = Entity> + '_ {
+        self.all_at(cell).filter(move |(_, entry_occupant)| *entry_occupant == occupant).map(|(entity, _)| entity)
+    }
+
+    /// Every entity on `cell`, whatever its kind.
+    pub fn all_at(&self, cell: UVec2) -> impl Iterator<Item = (Entity, TileOccupant)> + '_ {
+        self.buckets
+            .get(&(cell / Self::BUCKET_SIZE))
+            .into_iter()
+            .flatten()
+            .filter(move |entry| entry.cell == cell)
+            .map(|entry| (entry.entity, entry.occupant))
+    }
+
+    /// The entities of a kind no farther than `radius` cells from `center`, nearest first.
+    pub fn within_radius(&self, center: UVec2, radius: f32, occupant: TileOccupant) -> Vec<(Entity, UVec2)> {
+        let reach = radius.max(0.0).floor() as u32;
+        let min = center.saturating_sub(UVec2::splat(reach)) / Self::BUCKET_SIZE;
+        let max = center.saturating_add(UVec2::splat(reach)) / Self::BUCKET_SIZE;
+        let mut found: Vec<_> = (min.y..=max.y)
+            .flat_map(|y| (min.x..=max.x).map(move |x| UVec2::new(x, y)))
+            .filter_map(|bucket| self.buckets.get(&bucket))
+            .flatten()
+            .filter(|entry| entry.occupant == occupant && distance_squared(center, entry.cell) as f32 <= radius * radius)
+            .map(|entry| (entry.entity, entry.cell))
+            .collect();
+        sort_by_distance(&mut found, center);
+        found
+    }
+
+    /// The `k` entities of a kind nearest to `center`, nearest first. Fewer when there aren't as many.
+    pub fn nearest(&self, center: UVec2, k: usize, occupant: TileOccupant) -> Vec<(Entity, UVec2)> {
+        if k == 0 {
+            return vec![];
+        }
+        let center_bucket = (center / Self::BUCKET_SIZE).as_ivec2();
+        // no bucket past this ring around the center's bucket has entries
+        let last_ring = self
+            .buckets
+            .keys()
+            .map(|bucket| (bucket.as_ivec2() - center_bucket).abs().max_element())
+            .max()
+            .unwrap_or(0);
+
+        let mut found = vec![];
+        for ring in 0..=last_ring {
+            for bucket in ring_around(center_bucket, ring) {
+                let Some(entries) = self.buckets.get(&bucket) else {
+                    continue;
+                };
+                found.extend(entries.iter().filter(|entry| entry.occupant == occupant).map(|entry| (entry.entity, entry.cell)));
+            }
+            // cells in the rings further out are at least this far from the center
+            let closest_unseen = (ring as u64 * Self::BUCKET_SIZE as u64 + 1).pow(2);
+            if found.len() >= k {
+                sort_by_distance(&mut found, center);
+                if distance_squared(center, found[k - 1].1) <= closest_unseen {
+                    break;
+                }
+            }
+        }
+        sort_by_distance(&mut found, center);
+        found.truncate(k);
+        found
+    }
+}
+
+fn distance_squared(a: UVec2, b: UVec2) -> u64 {
+    let delta = a.as_i64vec2() - b.as_i64vec2();
+    (delta.x * delta.x + delta.y * delta.y) as u64
+}
+
+/// Sorts by distance to `center`, ties by entity so equal queries give equal answers.
+fn sort_by_distance(found: &mut [(Entity, UVec2)], center: UVec2) {
+    found.sort_by_key(|(entity, cell)| (distance_squared(center, *cell), *entity));
+}
+
+/// The buckets `ring` buckets away from `center` along either axis, the ones off the map left out.
+fn ring_around(center: IVec2, ring: i32) -> impl Iterator<Item = UVec2> {
+    (-ring..=ring)
+        .flat_map(move |dy| (-ring..=ring).map(move |dx| IVec2::new(dx, dy)))
+        .filter(move |offset| offset.abs().max_element() == ring)
+        .map(move |offset| center + offset)
+        .filter(|bucket| bucket.cmpge(IVec2::ZERO).all())
+        .map(|bucket| bucket.as_uvec2())
+}
@@ -1,2 +1,18 @@
 pub mod ui;
-pub mod map_generation;
\ No newline at end of file
+pub mod map_generation;
+pub mod lighting;
+pub mod asset_loading;
+pub mod hot_reload;
+pub mod settings;
+pub mod input;
+pub mod camera;
+pub mod menu;
+pub mod rivers;
+pub mod transitions;
+pub mod tile_textures;
+pub mod mining;
+pub mod path_finding;
+pub mod spatial_index;
+pub mod occupancy;
+pub mod job_assignment;
+pub mod walking;
\ No newline at end of file
@@ -0,0 +1,265 @@
+use crate::plugins::game_world_plugin::GameState;
+use crate::plugins::validated_json_asset_plugin::{schema_validator, schema_violations};
+use crate::resources::game_errors::GameErrors;
+use crate::resources::mods::{mod_folder_of, ActiveMods, ModManifest, NamedDefinition, MODS_FOLDER};
+use crate::GameWorld;
+use bevy::asset::io::{AssetSourceId, ErasedAssetReader};
+use bevy::asset::{AssetLoadError, AsyncReadExt};
+use bevy::tasks::futures_lite::{future, StreamExt};
+use bevy::{prelude::*, tasks::*};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+const MOD_MANIFEST: &str = "mod.json";
+
+/// Lists every file under the asset root and picks the mods to load, so the files can be loaded and tracked one by one.
+#[derive(Resource)]
+pub struct AssetListingTask(Task<Result<(Vec<PathBuf>, ActiveMods), String>>);
+
+/// Every asset file the game loads, with how far along each one is. The base game's files come first,
+/// then every active mod's in load order.
+#[derive(Resource, Default)]
+pub struct LoadingAssets(Vec<(PathBuf, AssetLoad)>);
+
+pub enum AssetLoad {
+    Loading(Task<Result<UntypedHandle, AssetLoadError>>),
+    Loaded(UntypedHandle),
+    Failed(String),
+    /// No loader handles this file, like `load_folder` these are ignored.
+    Skipped,
+}
+
+#[derive(Event)]
+pub struct RetryFailedAssets;
+
+impl LoadingAssets {
+    /// How many assets finished loading, out of the ones that can be loaded.
+    pub fn progress(&self) -> (usize, usize) {
+        let loaded = self.0.iter().filter(|(_, load)| matches!(load, AssetLoad::Loaded(_))).count();
+        let total = self.0.iter().filter(|(_, load)| !matches!(load, AssetLoad::Skipped)).count();
+        (loaded, total)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.0.iter().filter_map(|(path, load)| match load {
+            AssetLoad::Failed(error) => Some((path.as_path(), error.as_str())),
+            _ => None,
+        })
+    }
+
+    pub fn handles(&self) -> Vec<UntypedHandle> {
+        self.0
+            .iter()
+            .filter_map(|(_, load)| match load {
+                AssetLoad::Loaded(handle) => Some(handle.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The loaded assets of one type, in load order, with the path each was loaded from.
+    pub fn in_load_order<'a, A: Asset>(&'a self, assets: &'a Assets<A>) -> impl Iterator<Item = (&'a Path, &'a A)> {
+        self.0.iter().filter_map(|(path, load)| match load {
+            AssetLoad::Loaded(handle) => Some((path.as_path(), assets.get(handle.id().try_typed::<A>().ok()?)?)),
+            _ => None,
+        })
+    }
+
+    /// The definition named `name` from the last file in load order that has one.
+    pub fn definition<'a, A: Asset + NamedDefinition>(&'a self, assets: &'a Assets<A>, name: &str) -> Option<&'a A> {
+        self.in_load_order(assets)
+            .map(|(_, definition)| definition)
+            .filter(|definition| definition.definition_name() == name)
+            .last()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.0.iter().all(|(_, load)| matches!(load, AssetLoad::Loaded(_) | AssetLoad::Skipped))
+    }
+}
+
+pub fn start_load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let asset_server = asset_server.clone();
+    let task = IoTaskPool::get().spawn(async move {
+        let source = asset_server
+            .get_source(AssetSourceId::Default)
+            .map_err(|error| error.to_string())?;
+        let reader = source.reader();
+        let mut files = vec![];
+        list_asset_files(reader, Path::new(""), &mut files).await?;
+        files.sort();
+
+        let (mod_files, mut paths): (Vec<_>, Vec<_>) =
+            files.into_iter().partition(|path| path.starts_with(MODS_FOLDER));
+
+        // every folder directly under mods is a mod
+        let mut mod_folders: Vec<PathBuf> = mod_files
+            .iter()
+            .filter(|path| path.components().count() > 2)
+            .filter_map(|path| mod_folder_of(path))
+            .collect();
+        mod_folders.dedup();
+
+        let mut found = vec![];
+        for folder in mod_folders {
+            let manifest_path = folder.join(MOD_MANIFEST);
+            let manifest = match mod_files.contains(&manifest_path) {
+                true => read_mod_manifest(reader, &manifest_path).await,
+                false => Err(format!("There is no {MOD_MANIFEST} in this folder")),
+            };
+            found.push((folder, manifest));
+        }
+        let active_mods = ActiveMods::resolve(found);
+
+        for active_mod in &active_mods.mods {
+            let manifest_path = active_mod.folder.join(MOD_MANIFEST);
+            paths.extend(
+                mod_files
+                    .iter()
+                    .filter(|path| path.starts_with(&active_mod.folder) && **path != manifest_path)
+                    .cloned(),
+            );
+        }
+
+        Ok((paths, active_mods))
+    });
+
+    commands.insert_resource(AssetListingTask(task));
+}
+
+async fn list_asset_files(
+    reader: &dyn ErasedAssetReader,
+    path: &Path,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let mut children = reader
+        .read_directory(path)
+        .await
+        .map_err(|error| format!("Could not read the {} asset folder: {error}", path.display()))?;
+
+    while let Some(child_path) = children.next().await {
+        let is_directory = reader.is_directory(&child_path).await.map_err(|error| error.to_string())?;
+        if is_directory {
+            Box::pin(list_asset_files(reader, &child_path, paths)).await?;
+        } else {
+            paths.push(child_path);
+        }
+    }
+
+    Ok(())
+}
+
+async fn read_mod_manifest(reader: &dyn ErasedAssetReader, path: &Path) -> Result<ModManifest, String> {
+    let mut bytes = vec![];
+    let mut file = reader
+        .read(path)
+        .await
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+    file.read_to_end(&mut bytes)
+        .await
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+
+    let value: Value = serde_json::from_slice(&bytes)
+        .map_err(|error| format!("{} is not valid JSON: {error}", path.display()))?;
+    let violations = schema_violations(&schema_validator(MOD_MANIFEST)?, &value);
+    if !violations.is_empty() {
+        return Err(format!("{} doesn't match its schema: {}", path.display(), violations.join("; ")));
+    }
+
+    serde_json::from_value(value).map_err(|error| format!("{} could not be read: {error}", path.display()))
+}
+
+fn start_asset_load(asset_server: &AssetServer, path: &Path) -> AssetLoad {
+    let asset_server = asset_server.clone();
+    let path = path.to_path_buf();
+    AssetLoad::Loading(IoTaskPool::get().spawn(async move { asset_server.load_untyped_async(path).await }))
+}
+
+pub fn check_asset_listing(
+    mut commands: Commands,
+    task: Option<ResMut<AssetListingTask>>,
+    asset_server: Res<AssetServer>,
+    mut game_errors: ResMut<GameErrors>,
+) {
+    let Some(mut task) = task else {
+        return;
+    };
+    let Some(result) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    commands.remove_resource::<AssetListingTask>();
+
+    match result {
+        Ok((paths, active_mods)) => {
+            for active_mod in &active_mods.mods {
+                info!("Loading mod {} {}", active_mod.manifest.id, active_mod.manifest.version);
+            }
+            for (folder, reason) in &active_mods.disabled {
+                warn!("Skipping the mod in {}: {reason}", folder.display());
+            }
+            commands.insert_resource(active_mods);
+
+            let loads = paths
+                .into_iter()
+                .map(|path| {
+                    let load = start_asset_load(&asset_server, &path);
+                    (path, load)
+                })
+                .collect();
+            commands.insert_resource(LoadingAssets(loads));
+        }
+        Err(error) => game_errors.0.push(error),
+    }
+}
+
+pub fn check_assets_loaded(
+    loading_assets: Option<ResMut<LoadingAssets>>,
+    mut game_world: ResMut<GameWorld>,
+    mut app_next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut loading_assets) = loading_assets else {
+        return;
+    };
+
+    for (path, load) in loading_assets.0.iter_mut() {
+        let AssetLoad::Loading(task) = load else {
+            continue;
+        };
+        let Some(result) = block_on(future::poll_once(task)) else {
+            continue;
+        };
+
+        *load = match result {
+            Ok(handle) => AssetLoad::Loaded(handle),
+            Err(AssetLoadError::MissingAssetLoaderForExtension(_)) => AssetLoad::Skipped,
+            Err(error) => {
+                error!("Failed to load {}: {error}", path.display());
+                AssetLoad::Failed(error.to_string())
+            }
+        };
+    }
+
+    if loading_assets.is_finished() {
+        game_world.set_biome_textures(&loading_assets.handles());
+        app_next_state.set(GameState::MainMenu);
+    }
+}
+
+pub fn retry_failed_assets(
+    mut events: EventReader<RetryFailedAssets>,
+    loading_assets: Option<ResMut<LoadingAssets>>,
+    asset_server: Res<AssetServer>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(mut loading_assets) = loading_assets else {
+        return;
+    };
+
+    for (path, load) in loading_assets.0.iter_mut() {
+        if matches!(load, AssetLoad::Failed(_)) {
+            *load = start_asset_load(&asset_server, path);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::resources::input_actions::{ActionState, InputAction};
+use bevy::prelude::*;
+
+/// How fast the camera pans at the default zoom, in pixels per second.
+const CAMERA_PAN_SPEED: f32 = 800.0;
+const CAMERA_ZOOM_SPEED: f32 = 1.5;
+const CAMERA_MIN_SCALE: f32 = 0.25;
+const CAMERA_MAX_SCALE: f32 = 4.0;
+
+pub fn pan_camera(
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+) {
+    let mut direction = Vec2::ZERO;
+    for (action, step) in [
+        (InputAction::PanCameraUp, Vec2::Y),
+        (InputAction::PanCameraDown, Vec2::NEG_Y),
+        (InputAction::PanCameraLeft, Vec2::NEG_X),
+        (InputAction::PanCameraRight, Vec2::X),
+    ] {
+        if actions.pressed(action) {
+            direction += step;
+        }
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    for (mut transform, projection) in camera_query.iter_mut() {
+        // zoomed out, the same distance on screen covers more of the world
+        let distance = direction.normalize() * CAMERA_PAN_SPEED * projection.scale * time.delta_seconds();
+        transform.translation += distance.extend(0.0);
+    }
+}
+
+pub fn zoom_camera(
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut camera_query: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    let zoom = match (actions.pressed(InputAction::ZoomIn), actions.pressed(InputAction::ZoomOut)) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => return,
+    };
+
+    for mut projection in camera_query.iter_mut() {
+        projection.scale = (projection.scale * (1.0 + zoom * CAMERA_ZOOM_SPEED * time.delta_seconds()))
+            .clamp(CAMERA_MIN_SCALE, CAMERA_MAX_SCALE);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::components::map_chunk::MapChunk;
+use crate::components::transition_overlay::TransitionOverlay;
+use crate::resources::biome_registry::BiomeRegistry;
+use crate::resources::biomes::Biomes;
+use crate::resources::game_map::GameMap;
+use crate::resources::game_resources::GameResource;
+use crate::resources::path_finder::PathFinder;
+use crate::systems::asset_loading::LoadingAssets;
+use crate::systems::tile_textures::{unmatched_tile_weights, TilePicker};
+use crate::systems::transitions::{despawn_transition_overlays, spawn_transition_overlays};
+use crate::GameWorld;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::map::TilemapId;
+use bevy_ecs_tilemap::tiles::{TilePos, TileStorage, TileTextureIndex};
+
+/// Applies edited biome definitions to the running map: movement modifiers and the paths over them are recomputed,
+/// the tiles of biomes whose tile name or tile weights changed get new textures and the transitions are blended
+/// again when they could have changed. The map itself stays. Asset files are only watched with the `dev` feature.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_biome_changes(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Biomes>>,
+    biomes: Res<Assets<Biomes>>,
+    loading_assets: Res<LoadingAssets>,
+    mut biome_registry: ResMut<BiomeRegistry>,
+    game_map: Option<ResMut<GameMap>>,
+    game_world: Res<GameWorld>,
+    chunk_query: Query<&MapChunk>,
+    mut tile_query: Query<(&TilePos, &TilemapId, &mut TileTextureIndex)>,
+    overlay_query: Query<(Entity, &TileStorage), With<TransitionOverlay>>,
+    mut images: ResMut<Assets<Image>>,
+    path_finder: Option<ResMut<PathFinder>>,
+) {
+    let Some(mut game_map) = game_map else {
+        return;
+    };
+
+    // any table changing can change the merged biomes, rebuild them once
+    if !events.read().any(|event| matches!(event, AssetEvent::Modified { .. })) {
+        return;
+    }
+
+    let reloaded = match BiomeRegistry::from_tables(loading_assets.in_load_order(&biomes)) {
+        Ok(reloaded) => reloaded,
+        Err(errors) => {
+            for error in errors {
+                warn!("{error}");
+            }
+            warn!("The edited biome tables are invalid, keeping the current biomes");
+            return;
+        }
+    };
+    biome_registry.update(reloaded);
+
+    let previous_transitions: Vec<_> = game_map.biomes().iter().map(|biome| biome.transitions.clone()).collect();
+    let retextured = game_map.update_biomes(&biome_registry);
+    if let Some(mut path_finder) = path_finder {
+        *path_finder = PathFinder::new(&game_map);
+    }
+    info!("Reloaded the biome table, {} biomes changed tiles", retextured.len());
+    let transitions_changed = game_map
+        .biomes()
+        .iter()
+        .zip(&previous_transitions)
+        .any(|(biome, previous)| biome.transitions != *previous);
+    if !retextured.is_empty() || transitions_changed {
+        despawn_transition_overlays(&mut commands, &overlay_query);
+        spawn_transition_overlays(&mut commands, &game_map, &game_world, &mut images);
+    }
+    if retextured.is_empty() {
+        return;
+    }
+
+    for error in unmatched_tile_weights(&game_world, game_map.biomes()) {
+        warn!("{error}");
+    }
+    let tile_picker = TilePicker::new(&game_world, game_map.biomes(), game_map.seed());
+    for (tile_pos, tilemap_id, mut texture_index) in tile_query.iter_mut() {
+        let Ok(chunk) = chunk_query.get(tilemap_id.0) else {
+            continue;
+        };
+        let cell = chunk.cell(tile_pos);
+        let biome_id = game_map.biome_id_at(cell.x, cell.y);
+        if !retextured.contains(&biome_id) {
+            continue;
+        }
+        match tile_picker.pick(biome_id, cell.x, cell.y) {
+            Some(index) => texture_index.0 = index,
+            None => warn!("Biome {} has no tile textures", biome_registry.get(biome_id).name),
+        }
+    }
+}
+
+/// Resource stats are always read from `Assets<GameResource>`, so edits apply without any work here.
+pub fn log_game_resource_changes(
+    mut events: EventReader<AssetEvent<GameResource>>,
+    game_resources: Res<Assets<GameResource>>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(game_resource) = game_resources.get(*id) {
+                info!("Reloaded resource {}", game_resource.name);
+            }
+        }
+    }
+}
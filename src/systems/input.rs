@@ -0,0 +1,31 @@
+use crate::resources::input_actions::ActionState;
+use crate::resources::settings::Settings;
+use crate::systems::settings::OptionsScreen;
+use bevy::prelude::*;
+
+/// Nothing is pressed while a new binding is being picked, so the input pressed for it does nothing else.
+pub fn update_action_state(
+    mut action_state: ResMut<ActionState>,
+    settings: Res<Settings>,
+    options_screen: Res<OptionsScreen>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+) {
+    if options_screen.is_rebinding() {
+        action_state.update(&settings.keybindings, &ButtonInput::default(), &ButtonInput::default());
+        return;
+    }
+    action_state.update(&settings.keybindings, &keys, &mouse_buttons);
+}
+
+/// Hand-edited settings can bind one input to several actions, which then all happen at once.
+pub fn report_binding_conflicts(settings: Res<Settings>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (binding, actions) in settings.keybindings.conflicts() {
+        let labels: Vec<_> = actions.iter().map(|action| action.label()).collect();
+        warn!("{binding} is bound to more than one action: {}", labels.join(", "));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::components::assigned_job::AssignedJob;
+use crate::components::walk_path::WalkPath;
+use crate::components::work_priorities::WorkPriorities;
+use crate::resources::jobs::{JobEvent, JobState, Jobs};
+use crate::resources::path_finder::PathFinder;
+use crate::resources::spatial_index::SpatialIndex;
+use bevy::prelude::*;
+
+/// Cancels the jobs whose target is gone for good, and fails the ones the pawn that took them can't walk up to
+/// for now. Jobs taken by pawns that are gone are opened again, and so are failed jobs once their wait is over.
+pub fn check_jobs(
+    time: Res<Time>,
+    mut jobs: ResMut<Jobs>,
+    spatial_index: Res<SpatialIndex>,
+    path_finder: Res<PathFinder>,
+    pawn_query: Query<(), With<WorkPriorities>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    jobs.reopen_due(now);
+
+    let checks: Vec<_> = jobs
+        .iter()
+        .filter(|job| !matches!(job.state(), JobState::Failed { .. }))
+        .map(|job| (job.id(), job.holder(), job.job_type.target(&spatial_index)))
+        .collect();
+    for (id, holder, target) in checks {
+        let Some(target) = target else {
+            _ = jobs.cancel(id);
+            continue;
+        };
+        let Some(holder) = holder else {
+            continue;
+        };
+        if !pawn_query.contains(holder) {
+            _ = jobs.release(id, holder);
+            continue;
+        }
+        let reachable = spatial_index.cell_of(holder).is_some_and(|cell| path_finder.is_reachable_next_to(cell, target));
+        if !reachable {
+            _ = jobs.fail(id, "It can't be reached", now);
+        }
+    }
+}
+
+/// Has every pawn without a job reserve the open job it prefers: the kind of work it puts first, then the
+/// nearest. Jobs the pawn can't walk up to are left for others, and so are the kinds no pawn carries out yet.
+/// Pawns let go of jobs they stopped doing, and stop walking to jobs they no longer have.
+pub fn assign_jobs(
+    mut commands: Commands,
+    mut jobs: ResMut<Jobs>,
+    spatial_index: Res<SpatialIndex>,
+    path_finder: Res<PathFinder>,
+    pawn_query: Query<(Entity, &WorkPriorities, Option<&AssignedJob>)>,
+) {
+    let mut idle = vec![];
+    for (pawn, priorities, assigned_job) in pawn_query.iter() {
+        if let Some(AssignedJob(id)) = assigned_job {
+            let held = jobs.get(*id).filter(|job| job.holder() == Some(pawn));
+            match held.map(|job| priorities.get(job.job_type.work_type()).is_some()) {
+                Some(true) => continue,
+                Some(false) => _ = jobs.release(*id, pawn),
+                // it's done, failed or went to another pawn
+                None => {}
+            }
+            commands.entity(pawn).remove::<(AssignedJob, WalkPath)>();
+        }
+        idle.push((pawn, priorities));
+    }
+    idle.sort_by_key(|(pawn, _)| *pawn);
+
+    for (pawn, priorities) in idle {
+        let Some(cell) = spatial_index.cell_of(pawn) else {
+            continue;
+        };
+        let best = jobs
+            .iter()
+            .filter(|job| *job.state() == JobState::Open && job.job_type.is_carried_out())
+            .filter_map(|job| {
+                let priority = priorities.get(job.job_type.work_type())?;
+                let target = job.job_type.target(&spatial_index)?;
+                path_finder.is_reachable_next_to(cell, target).then(|| {
+                    let distance = target.as_ivec2().distance_squared(cell.as_ivec2());
+                    (priority, distance, job.id())
+                })
+            })
+            // the first posted wins a tie
+            .min_by_key(|(priority, distance, _)| (*priority, *distance));
+
+        if let Some((_, _, id)) = best {
+            if jobs.reserve(id, pawn).is_ok() {
+                commands.entity(pawn).insert(AssignedJob(id));
+            }
+        }
+    }
+}
+
+pub fn send_job_events(mut jobs: ResMut<Jobs>, mut events: EventWriter<JobEvent>) {
+    events.send_batch(jobs.drain_events());
+}
@@ -0,0 +1,143 @@
+use crate::components::{light_source::LightSource, plant::Plant};
+use crate::resources::{day_cycle::DayCycle, plants::PlantDefinition};
+use crate::systems::asset_loading::LoadingAssets;
+use crate::GameWorld;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+const LIGHT_GLOW_TEXTURE_SIZE: u32 = 64;
+
+/// Above every world sprite, so the overlay tints the tilemap, plants and pawns alike.
+const AMBIENT_OVERLAY_Z: f32 = 10.0;
+const LIGHT_GLOW_Z: f32 = AMBIENT_OVERLAY_Z + 1.0;
+
+#[derive(Resource)]
+pub struct LightGlowTexture(Handle<Image>);
+
+#[derive(Component)]
+pub struct AmbientOverlay;
+
+#[derive(Component)]
+pub struct LightGlow;
+
+pub fn create_light_glow_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // white radial gradient, the glow sprite colors it with the light's color
+    let size = LIGHT_GLOW_TEXTURE_SIZE;
+    let center = (size as f32 - 1.0) / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = Vec2::new(x as f32 - center, y as f32 - center).length() / center;
+            let alpha = (1.0 - distance).clamp(0.0, 1.0).powi(2);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+
+    let image = Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    commands.insert_resource(LightGlowTexture(images.add(image)));
+}
+
+pub fn create_ambient_overlay(mut commands: Commands, game_world: Res<GameWorld>) {
+    // a single sprite covering the whole map, with some margin for the edges of the screen
+    let size = Vec2::new(game_world.width() as f32, game_world.height() as f32)
+        * game_world.cell_size() as f32
+        * 2.0;
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::NONE,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0., 0., AMBIENT_OVERLAY_Z)),
+            ..default()
+        },
+        Pickable::IGNORE,
+        AmbientOverlay,
+        Name::new("Ambient Overlay"),
+    ));
+}
+
+pub fn advance_day_cycle(time: Res<Time>, mut day_cycle: ResMut<DayCycle>) {
+    day_cycle.advance(time.delta_seconds());
+}
+
+pub fn update_ambient_overlay(
+    day_cycle: Res<DayCycle>,
+    mut overlay_query: Query<&mut Sprite, With<AmbientOverlay>>,
+) {
+    let color = day_cycle.ambient_color();
+    for mut sprite in overlay_query.iter_mut() {
+        sprite.color = color;
+    }
+}
+
+pub fn attach_plant_lights(
+    mut commands: Commands,
+    plant_query: Query<(Entity, &Plant), Added<Plant>>,
+    plant_definitions: Res<Assets<PlantDefinition>>,
+    loading_assets: Res<LoadingAssets>,
+) {
+    for (entity, plant) in plant_query.iter() {
+        let light = loading_assets
+            .definition(&plant_definitions, &plant.name)
+            .and_then(|definition| definition.light.clone());
+
+        if let Some(light) = light {
+            commands.entity(entity).insert(LightSource(light));
+        }
+    }
+}
+
+pub fn spawn_light_glows(
+    mut commands: Commands,
+    light_query: Query<(Entity, &LightSource), Added<LightSource>>,
+    light_glow_texture: Res<LightGlowTexture>,
+    game_world: Res<GameWorld>,
+) {
+    for (entity, LightSource(light)) in light_query.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    texture: light_glow_texture.0.clone(),
+                    sprite: Sprite {
+                        color: Color::NONE,
+                        custom_size: Some(Vec2::splat(
+                            light.range * 2.0 * game_world.cell_size() as f32,
+                        )),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(0., 0., LIGHT_GLOW_Z)),
+                    ..default()
+                },
+                Pickable::IGNORE,
+                LightGlow,
+            ));
+        });
+    }
+}
+
+pub fn update_light_glows(
+    day_cycle: Res<DayCycle>,
+    light_query: Query<(&LightSource, &Children)>,
+    mut glow_query: Query<&mut Sprite, With<LightGlow>>,
+) {
+    let night_factor = day_cycle.night_factor();
+    for (LightSource(light), children) in light_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut sprite) = glow_query.get_mut(*child) {
+                let color = light.color.0;
+                sprite.color = color.with_alpha(color.alpha() * night_factor);
+            }
+        }
+    }
+}
@@ -1,17 +1,134 @@
+use crate::components::map_chunk::MapChunk;
 use crate::plugins::game_world_plugin::GameState;
+use crate::resources::biome_registry::{BiomeId, BiomeRegistry};
 use crate::resources::biomes::{Biome, Biomes};
+use crate::resources::game_errors::GameErrors;
+use crate::resources::game_resources::GameResource;
+use crate::resources::game_map::GameMap;
+use crate::resources::new_game::NewGameSettings;
+use crate::systems::asset_loading::LoadingAssets;
+use crate::systems::rivers::{carve_rivers, RiverParameters};
+use crate::systems::tile_textures::{unmatched_tile_weights, TilePicker};
+use crate::systems::transitions::spawn_transition_overlays;
 use crate::GameWorld;
 use bevy::tasks::futures_lite::future;
+use bevy::render::primitives::Aabb;
 use bevy::{prelude::*, tasks::*};
 use bevy_ecs_tilemap::map::{TilemapId, TilemapSize, TilemapTexture, TilemapTileSize, TilemapType};
 use bevy_ecs_tilemap::prelude::get_tilemap_center_transform;
 use bevy_ecs_tilemap::tiles::{TileBundle, TilePos, TileStorage, TileTextureIndex};
 use bevy_ecs_tilemap::TilemapBundle;
 use noise::{NoiseFn, OpenSimplex};
-use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::ops::Range;
+use std::sync::Arc;
 
+/// How many rows of the map each generation task works on.
+const ROWS_PER_CHUNK: u32 = 16;
+
+/// How tightly the ore veins wind, the frequency the ore noise is sampled at.
+const ORE_FREQUENCY: f64 = 0.06;
+/// Veins run along where the ore noise crosses 0, a cell is ore when the noise is within its biome's
+/// abundance times this of it. Makes the abundance about the share of the host that's ore.
+const ORE_SPREAD: f64 = 0.37;
+/// Each ore samples the ore noise this far from the previous one, so their veins don't line up.
+const ORE_OFFSET: f64 = 1000.0;
+
+/// The map's tiles are drawn below everything standing on them.
+pub(crate) const MAP_Z: f32 = -1.0;
+
+/// Everything that decides which map gets generated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapParameters {
+    pub width: u32,
+    pub height: u32,
+    pub seed: u64,
+    /// How common each biome is, indexed by biome id, see `NewGameSettings::biome_weights`.
+    pub biome_weights: Vec<f64>,
+    pub rivers: RiverParameters,
+}
+
+/// How far a map generation got, shared with the thread generating it.
+#[derive(Default)]
+pub struct MapGenerationProgress {
+    rows_done: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+impl MapGenerationProgress {
+    pub fn rows_done(&self) -> u32 {
+        self.rows_done.load(Ordering::Relaxed)
+    }
+
+    /// Stops the generation after the row it's working on.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A map being generated on the task pool. Dropping it cancels the generation.
 #[derive(Resource)]
-pub struct MapGenerationTask(Task<Vec<MapGenerationCell>>);
+pub struct MapGenerationTask {
+    parameters: MapParameters,
+    task: Task<Option<Vec<MapGenerationCell>>>,
+    progress: Arc<MapGenerationProgress>,
+}
+
+impl MapGenerationTask {
+    pub fn spawn(biomes: Vec<Biome>, parameters: MapParameters) -> Self {
+        let progress = Arc::new(MapGenerationProgress::default());
+        let task_progress = progress.clone();
+        let task_parameters = parameters.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            generate_map_with_progress(&biomes, &task_parameters, &task_progress)
+        });
+
+        Self { parameters, task, progress }
+    }
+
+    pub fn parameters(&self) -> &MapParameters {
+        &self.parameters
+    }
+
+    /// How much of the map is done, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.progress.rows_done() as f32 / self.parameters.height.max(1) as f32
+    }
+
+    /// The generated cells once the map is done. The task is finished after that, don't poll it again.
+    pub fn poll(&mut self) -> Option<Vec<MapGenerationCell>> {
+        block_on(future::poll_once(&mut self.task)).flatten()
+    }
+}
+
+impl Drop for MapGenerationTask {
+    fn drop(&mut self) {
+        // dropping the task only stops it at an await point, and generation has none
+        self.progress.cancel();
+    }
+}
+
+/// The map shown on the new game screen, generated again whenever the player changes the settings.
+#[derive(Resource, Default)]
+pub struct MapPreview {
+    /// The generation for the current settings, while it runs.
+    pub generation: Option<MapGenerationTask>,
+    /// The last finished map, with the parameters it was generated with.
+    pub map: Option<(MapParameters, Vec<MapGenerationCell>)>,
+}
+
+impl MapPreview {
+    /// Whether the finished map is the one for `parameters`.
+    pub fn is_ready(&self, parameters: &MapParameters) -> bool {
+        self.map.as_ref().is_some_and(|(map_parameters, _)| map_parameters == parameters)
+    }
+}
 
 struct MapGenerationWave {
     frequency: f64,
@@ -19,10 +136,14 @@ struct MapGenerationWave {
     noise: OpenSimplex,
 }
 
-#[derive(Default, Clone)]
-struct MapGenerationCell {
-    height: f64,
-    biome_index: i32,
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct MapGenerationCell {
+    pub height: f64,
+    pub moisture: f64,
+    pub heat: f64,
+    pub biome_id: BiomeId,
+    /// False when no biome's thresholds matched and the closest biome was used instead.
+    pub matched: bool,
 }
 
 impl Biome {
@@ -37,84 +158,290 @@ impl Biome {
     }
 }
 
-pub fn start_map_generation(
+/// Merges the loaded biome tables, so the new game screen can list the biomes before a map is generated.
+pub fn register_biomes(
     mut commands: Commands,
     biomes: Res<Assets<Biomes>>,
+    game_resources: Res<Assets<GameResource>>,
+    loading_assets: Res<LoadingAssets>,
     game_world: Res<GameWorld>,
+    mut game_errors: ResMut<GameErrors>,
+) {
+    if biomes.is_empty() {
+        game_errors.0.push("No biome table was loaded, there should be a *.biomes.json file in the biomes folder".to_string());
+        return;
+    }
+    let biome_registry = match BiomeRegistry::from_tables(loading_assets.in_load_order(&biomes)) {
+        Ok(biome_registry) => biome_registry,
+        Err(errors) => {
+            game_errors.0.extend(errors);
+            return;
+        }
+    };
+
+    let missing_textures: Vec<_> = biome_registry
+        .biomes()
+        .iter()
+        .filter(|biome| game_world.texture_handles_for_biome(biome).is_empty())
+        .map(|biome| format!("Biome {} has no tile textures in biomes/{}", biome.name, biome.tile_name()))
+        .collect();
+    if !missing_textures.is_empty() {
+        game_errors.0.extend(missing_textures);
+        return;
+    }
+
+    let unmatched_tile_weights = unmatched_tile_weights(&game_world, biome_registry.biomes());
+    if !unmatched_tile_weights.is_empty() {
+        game_errors.0.extend(unmatched_tile_weights);
+        return;
+    }
+
+    let unknown_drops: Vec<_> = biome_registry
+        .biomes()
+        .iter()
+        .flat_map(|biome| biome.mining.iter().flat_map(|mining| &mining.drops).map(move |drop| (biome, drop)))
+        .filter(|(_, drop)| loading_assets.definition(&game_resources, &drop.resource).is_none())
+        .map(|(biome, drop)| format!("Biome {} drops {}, but no resource is named that", biome.name, drop.resource))
+        .collect();
+    if !unknown_drops.is_empty() {
+        game_errors.0.extend(unknown_drops);
+        return;
+    }
+
+    commands.insert_resource(biome_registry);
+}
+
+/// Keeps the new game screen's preview in step with the settings, a change cancels the generation
+/// that's running and starts over.
+pub fn update_map_preview(
+    biome_registry: Res<BiomeRegistry>,
+    new_game_settings: Res<NewGameSettings>,
+    mut map_preview: ResMut<MapPreview>,
+) {
+    let parameters = new_game_settings.map_parameters(&biome_registry);
+    if map_preview.is_ready(&parameters) {
+        map_preview.generation = None;
+        return;
+    }
+
+    let map_preview = &mut *map_preview;
+    let generation = match map_preview.generation.as_mut() {
+        Some(generation) if *generation.parameters() == parameters => generation,
+        _ => map_preview
+            .generation
+            .insert(MapGenerationTask::spawn(biome_registry.biomes().to_vec(), parameters)),
+    };
+    if let Some(cells) = generation.poll() {
+        map_preview.map = Some((generation.parameters().clone(), cells));
+        map_preview.generation = None;
+    }
+}
+
+/// Starts on the map for the new game settings, taking the previewed one when it's done or underway.
+pub fn start_map_generation(
+    mut commands: Commands,
+    biome_registry: Res<BiomeRegistry>,
+    new_game_settings: Res<NewGameSettings>,
+    mut map_preview: ResMut<MapPreview>,
+    mut game_world: ResMut<GameWorld>,
+    mut images: ResMut<Assets<Image>>,
+    mut app_next_state: ResMut<NextState<GameState>>,
 ) {
-    let Some((_, biomes)) = biomes.iter().next() else {
-        panic!("Biomes not loaded")
+    let parameters = new_game_settings.map_parameters(&biome_registry);
+    game_world.set_size(parameters.width, parameters.height);
+
+    let MapPreview { generation, map } = std::mem::take(&mut *map_preview);
+    if let Some((_, cells)) = map.filter(|(map_parameters, _)| *map_parameters == parameters) {
+        finish_map(&mut commands, &biome_registry, &parameters, &cells, &game_world, &mut images);
+        app_next_state.set(GameState::Main);
+        return;
+    }
+
+    let task = match generation.filter(|generation| *generation.parameters() == parameters) {
+        Some(generation) => generation,
+        None => MapGenerationTask::spawn(biome_registry.biomes().to_vec(), parameters),
     };
-    let biomes = biomes.biomes.clone();
+    commands.insert_resource(task);
+}
+
+/// Generates the cell grid, in chunks of rows spread over the task pool. The same parameters always
+/// produce the same map. `biomes` are indexed by their id, like `BiomeRegistry::biomes`.
+pub fn generate_map(biomes: &[Biome], parameters: &MapParameters) -> Vec<MapGenerationCell> {
+    generate_map_with_progress(biomes, parameters, &MapGenerationProgress::default())
+        .expect("the generation was never cancelled")
+}
+
+/// Generates the whole grid on the calling thread, the chunked generation must match it cell for cell.
+pub fn generate_map_serially(biomes: &[Biome], parameters: &MapParameters) -> Vec<MapGenerationCell> {
+    let noise = MapNoise::new(parameters.seed);
+    let mut result = generate_rows(biomes, parameters, &noise, 0..parameters.height, &MapGenerationProgress::default())
+        .expect("the generation was never cancelled");
+    carve_rivers(&mut result, biomes, parameters);
+    result
+}
+
+/// Like `generate_map`, counting the finished rows in `progress` as it goes. Returns `None` when it
+/// was cancelled before the map was done.
+pub fn generate_map_with_progress(
+    biomes: &[Biome],
+    parameters: &MapParameters,
+    progress: &MapGenerationProgress,
+) -> Option<Vec<MapGenerationCell>> {
+    let noise = MapNoise::new(parameters.seed);
+    let row_chunks = (0..parameters.height)
+        .step_by(ROWS_PER_CHUNK as usize)
+        .map(|first_row| first_row..(first_row + ROWS_PER_CHUNK).min(parameters.height));
+
+    // the chunks come back in the order they were spawned, so they stitch together the same every time
+    let chunks = AsyncComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+        for rows in row_chunks {
+            let noise = &noise;
+            scope.spawn(async move { generate_rows(biomes, parameters, noise, rows, progress) });
+        }
+    });
 
-    let (width, height) = (game_world.width(), game_world.height());
+    let mut result = Vec::with_capacity((parameters.width * parameters.height) as usize);
+    for chunk in chunks {
+        result.extend(chunk?);
+    }
+    // rivers run across the whole map, so they're carved once the chunks are together
+    carve_rivers(&mut result, biomes, parameters);
+    Some(result)
+}
+
+/// The noise the map's height, moisture, heat and ore veins come from, all picked from the seed.
+struct MapNoise {
+    height_waves: Vec<MapGenerationWave>,
+    moisture_waves: Vec<MapGenerationWave>,
+    heat_waves: Vec<MapGenerationWave>,
+    ore: OpenSimplex,
+}
+
+impl MapNoise {
+    fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
 
-    let task_pool = AsyncComputeTaskPool::get();
-    let task = task_pool.spawn(async move {
         // set up the waves
         let height_waves = vec![
             MapGenerationWave {
                 frequency: 0.004,
                 amplitude: 1.0,
-                noise: OpenSimplex::new(rand::random()),
+                noise: OpenSimplex::new(rng.gen()),
             },
             MapGenerationWave {
                 frequency: 0.02,
                 amplitude: 0.5,
-                noise: OpenSimplex::new(rand::random()),
+                noise: OpenSimplex::new(rng.gen()),
             },
         ];
         let moisture_waves = vec![MapGenerationWave {
             frequency: 0.02,
             amplitude: 1.0,
-            noise: OpenSimplex::new(rand::random()),
+            noise: OpenSimplex::new(rng.gen()),
         }];
         let heat_waves = vec![
             MapGenerationWave {
                 frequency: 0.02,
                 amplitude: 1.0,
-                noise: OpenSimplex::new(rand::random()),
+                noise: OpenSimplex::new(rng.gen()),
             },
             MapGenerationWave {
                 frequency: 0.01,
                 amplitude: 0.5,
-                noise: OpenSimplex::new(rand::random()),
+                noise: OpenSimplex::new(rng.gen()),
             },
         ];
+        // picked last, so the rest of the map stays the same as before there were ores
+        let ore = OpenSimplex::new(rng.gen());
 
-        let mut result = vec![MapGenerationCell::default(); (width * height) as usize];
-        for x in 0..width {
-            for y in 0..height {
-                let height_value = generate_noise_value(x, y, &height_waves);
-                let moisture_value = generate_noise_value(x, y, &moisture_waves);
-                let heat_value = generate_noise_value(x, y, &heat_waves);
-
-                let mut best_biome_index = -1;
-                let mut best_difference = f64::MAX;
-
-                for biome_index in 0..biomes.len() {
-                    let biome = &biomes[biome_index];
-                    if biome.matches(height_value, moisture_value, heat_value) {
-                        let difference =
-                            biome.get_difference(height_value, moisture_value, heat_value);
-                        if difference < best_difference {
-                            best_biome_index = biome_index as i32;
-                            best_difference = difference;
-                        }
-                    }
-                }
-
-                result[(y * width + x) as usize] = MapGenerationCell {
-                    height: height_value,
-                    biome_index: best_biome_index,
-                };
-            }
+        Self { height_waves, moisture_waves, heat_waves, ore }
+    }
+}
+
+/// The cells of `rows`, row after row. Every cell only depends on its position and the noise,
+/// so rows can be generated in any order and on any thread.
+fn generate_rows(
+    biomes: &[Biome],
+    parameters: &MapParameters,
+    noise: &MapNoise,
+    rows: Range<u32>,
+    progress: &MapGenerationProgress,
+) -> Option<Vec<MapGenerationCell>> {
+    let mut result = Vec::with_capacity((parameters.width * rows.len() as u32) as usize);
+    for y in rows {
+        for x in 0..parameters.width {
+            let height_value = generate_noise_value(x, y, &noise.height_waves);
+            let moisture_value = generate_noise_value(x, y, &noise.moisture_waves);
+            let heat_value = generate_noise_value(x, y, &noise.heat_waves);
+
+            let (biome_id, matched) =
+                best_biome(biomes, &parameters.biome_weights, height_value, moisture_value, heat_value);
+            let biome_id = ore_vein(biomes, &parameters.biome_weights, noise, biome_id, x, y).unwrap_or(biome_id);
+
+            result.push(MapGenerationCell {
+                height: height_value,
+                moisture: moisture_value,
+                heat: heat_value,
+                biome_id,
+                matched,
+            });
         }
 
-        result
-    });
+        if progress.is_cancelled() {
+            return None;
+        }
+        progress.rows_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    Some(result)
+}
+
+/// The closest matching biome, heavier biomes counting as closer and biomes weighing 0 only used
+/// when no other biome matches. When none of them match, falls back to the closest biome overall,
+/// the earliest one winning ties, so unmatched cells are still deterministic.
+fn best_biome(biomes: &[Biome], biome_weights: &[f64], height: f64, moisture: f64, heat: f64) -> (BiomeId, bool) {
+    let weighted_difference = |index: usize, biome: &Biome| {
+        let difference = biome.get_difference(height, moisture, heat);
+        match biome_weights.get(index).copied().unwrap_or(1.0) {
+            weight if weight > 0.0 => (false, difference / weight),
+            _ => (true, difference),
+        }
+    };
+    let closest = |matching_only: bool| {
+        biomes
+            .iter()
+            .enumerate()
+            .filter(|(_, biome)| biome.is_placed_by_thresholds())
+            .filter(|(_, biome)| !matching_only || biome.matches(height, moisture, heat))
+            .map(|(index, biome)| (index, weighted_difference(index, biome)))
+            .min_by(|(_, (a_excluded, a)), (_, (b_excluded, b))| {
+                a_excluded.cmp(b_excluded).then(a.total_cmp(b))
+            })
+            .map(|(index, _)| BiomeId(index as u16))
+    };
 
-    commands.insert_resource(MapGenerationTask(task));
+    match closest(true) {
+        Some(biome_id) => (biome_id, true),
+        None => (closest(false).unwrap_or_default(), false),
+    }
+}
+
+/// The ore running through the cell at `x`, `y`, if any, the first one in the table winning where veins cross.
+/// A biome's weight makes its veins that much thicker.
+fn ore_vein(biomes: &[Biome], biome_weights: &[f64], noise: &MapNoise, host_id: BiomeId, x: u32, y: u32) -> Option<BiomeId> {
+    let host = &biomes[host_id.index()].name;
+    biomes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, biome)| Some((index, biome.vein.as_ref()?)))
+        .filter(|(_, vein)| vein.host == *host)
+        .find(|(index, vein)| {
+            let weight = biome_weights.get(*index).copied().unwrap_or(1.0);
+            let offset = *index as f64 * ORE_OFFSET;
+            let value = noise.ore.get([x as f64 * ORE_FREQUENCY + offset, y as f64 * ORE_FREQUENCY]);
+            value.abs() < vein.abundance * weight * ORE_SPREAD
+        })
+        .map(|(index, _)| BiomeId(index as u16))
 }
 
 fn generate_noise_value(x: u32, y: u32, waves: &[MapGenerationWave]) -> f64 {
@@ -123,86 +450,123 @@ fn generate_noise_value(x: u32, y: u32, waves: &[MapGenerationWave]) -> f64 {
 
     for wave in waves {
         result += wave.noise.get([
-            x as f64 * wave.frequency as f64,
-            y as f64 * wave.frequency as f64,
+            x as f64 * wave.frequency,
+            y as f64 * wave.frequency,
         ]) * wave.amplitude;
         normalization += wave.amplitude;
     }
 
-    result / normalization
+    // the noise is in [-1, 1], biome thresholds are in [0, 1]
+    ((result / normalization + 1.0) / 2.0).clamp(0.0, 1.0)
 }
 
 pub fn check_map_generation_finished(
     mut commands: Commands,
-    mut task: ResMut<MapGenerationTask>,
-    mut game_world: ResMut<GameWorld>,
-    biomes: Res<Assets<Biomes>>,
+    task: Option<ResMut<MapGenerationTask>>,
+    biome_registry: Res<BiomeRegistry>,
+    game_world: Res<GameWorld>,
+    mut images: ResMut<Assets<Image>>,
     mut app_next_state: ResMut<NextState<GameState>>,
 ) {
-    let status = block_on(future::poll_once(&mut task.0));
-    if let Some(result) = status {
+    // no task means the previewed map was used as it was
+    let Some(mut task) = task else {
+        return;
+    };
+
+    if let Some(result) = task.poll() {
         commands.remove_resource::<MapGenerationTask>();
-        setup_tileset(
-            commands,
-            &biomes.iter().next().unwrap().1.biomes,
-            &result,
-            &mut game_world,
-        );
+        finish_map(&mut commands, &biome_registry, task.parameters(), &result, &game_world, &mut images);
         app_next_state.set(GameState::Main);
     }
 }
 
-fn setup_tileset(
-    mut commands: Commands,
-    biomes: &Vec<Biome>,
-    result: &Vec<MapGenerationCell>,
-    game_world: &mut GameWorld,
+fn finish_map(
+    commands: &mut Commands,
+    biome_registry: &BiomeRegistry,
+    parameters: &MapParameters,
+    result: &[MapGenerationCell],
+    game_world: &GameWorld,
+    images: &mut Assets<Image>,
 ) {
-    let map_size = TilemapSize {
-        x: game_world.width(),
-        y: game_world.height(),
-    };
-    let mut tile_storage = TileStorage::empty(map_size);
-    let tilemap_entity = commands.spawn_empty().id();
-
-    for x in 0..game_world.width() {
-        for y in 0..game_world.height() {
-            let cell = &result[(y * game_world.width() + x) as usize];
-            let biome = &biomes[cell.biome_index as usize];
-            let tile_pos = TilePos { x, y };
-            let tile = commands.spawn((
-                TileBundle {
+    let game_map = GameMap::new(
+        game_world.width(),
+        game_world.height(),
+        parameters.seed,
+        biome_registry.biomes().to_vec(),
+        result.iter().map(|cell| cell.biome_id).collect(),
+    );
+    setup_tileset(commands, &game_map, game_world);
+    spawn_transition_overlays(commands, &game_map, game_world, images);
+    commands.insert_resource(game_map);
+}
+
+/// Spawns the map as square tilemap chunks. Every chunk has bounds, so the ones off screen are culled whole.
+fn setup_tileset(commands: &mut Commands, game_map: &GameMap, game_world: &GameWorld) {
+    let map_size = UVec2::new(game_map.width(), game_map.height());
+    let texture = TilemapTexture::Vector(game_world.texture_handles_for_all_biomes());
+    let tile_picker = TilePicker::new(game_world, game_map.biomes(), game_map.seed());
+
+    for chunk in MapChunk::covering(map_size) {
+        let chunk_size = chunk.size(map_size);
+        let mut tile_storage = TileStorage::empty(chunk_size.into());
+        let tilemap_entity = commands.spawn_empty().id();
+
+        for y in 0..chunk_size.y {
+            for x in 0..chunk_size.x {
+                let tile_pos = TilePos { x, y };
+                let cell = chunk.cell(&tile_pos);
+                let biome_id = game_map.biome_id_at(cell.x, cell.y);
+                let tile = commands.spawn(TileBundle {
                     position: tile_pos,
                     tilemap_id: TilemapId(tilemap_entity),
+                    texture_index: TileTextureIndex(tile_picker.pick(biome_id, cell.x, cell.y).unwrap()),
                     ..default()
-                },
-                TileTextureIndex(
-                    game_world
-                        .texture_handles_for_biome(biome)
-                        .choose(&mut game_world.rng())
-                        .unwrap()
-                        .1,
-                ),
-            ));
-            tile_storage.set(&tile_pos, tile.id());
+                });
+                tile_storage.set(&tile_pos, tile.id());
+            }
         }
+
+        commands.entity(tilemap_entity).insert((
+            chunk_tilemap(game_world, &chunk, chunk_size, tile_storage, texture.clone(), MAP_Z),
+            chunk,
+            Name::new("Map Chunk"),
+        ));
     }
+}
 
-    let tile_size = TilemapTileSize {
-        x: game_world.cell_size() as f32,
-        y: game_world.cell_size() as f32,
-    };
+/// A tilemap for the cells of `chunk`, laid out as if the chunks were one tilemap centered on the origin.
+/// It has bounds, so it's culled whole when it's off screen.
+pub(crate) fn chunk_tilemap(
+    game_world: &GameWorld,
+    chunk: &MapChunk,
+    chunk_size: UVec2,
+    storage: TileStorage,
+    texture: TilemapTexture,
+    z: f32,
+) -> (TilemapBundle, Aabb) {
+    let map_size = TilemapSize { x: game_world.width(), y: game_world.height() };
+    let tile_size = TilemapTileSize { x: game_world.cell_size() as f32, y: game_world.cell_size() as f32 };
     let grid_size = tile_size.into();
     let map_type = TilemapType::default();
+    let map_transform = get_tilemap_center_transform(&map_size, &grid_size, &map_type, z);
 
-    commands.entity(tilemap_entity).insert(TilemapBundle {
+    // tiles are centered on their position, so the first tile's center is the chunk's origin
+    let extents = chunk_size.as_vec2() * game_world.cell_size() as f32;
+    let bounds = Aabb::from_min_max(
+        (-Vec2::from(tile_size) / 2.0).extend(0.0),
+        (extents - Vec2::from(tile_size) / 2.0).extend(0.0),
+    );
+
+    let offset = chunk.origin.as_vec2() * game_world.cell_size() as f32;
+    let tilemap = TilemapBundle {
         grid_size,
         map_type,
-        size: map_size,
-        storage: tile_storage,
-        texture: TilemapTexture::Vector(game_world.texture_handles_for_all_biomes()),
+        size: chunk_size.into(),
+        storage,
+        texture,
         tile_size,
-        transform: get_tilemap_center_transform(&map_size, &grid_size, &map_type, 0.0),
+        transform: map_transform * Transform::from_translation(offset.extend(0.0)),
         ..default()
-    });
+    };
+    (tilemap, bounds)
 }
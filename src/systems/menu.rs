@@ -0,0 +1,213 @@
+use crate::plugins::game_world_plugin::GameState;
+use crate::resources::biome_registry::BiomeRegistry;
+use crate::resources::biomes::{Biome, BiomeKind};
+use crate::resources::game_errors::GameErrors;
+use crate::resources::new_game::NewGameSettings;
+use crate::resources::settings::Settings;
+use crate::systems::map_generation::{MapGenerationCell, MapParameters, MapPreview};
+use crate::systems::settings::OptionsScreen;
+use crate::systems::ui::show_game_errors;
+use crate::GameWorld;
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::egui::{
+    self, Align2, Button, Color32, ColorImage, DragValue, FontId, Grid, ProgressBar, RichText, Slider,
+    TextureHandle, TextureOptions, Ui,
+};
+use bevy_egui::EguiContexts;
+
+pub fn run_main_menu_ui(
+    mut ctx: EguiContexts,
+    game_errors: Res<GameErrors>,
+    settings: Res<Settings>,
+    mut options_screen: ResMut<OptionsScreen>,
+    mut app_next_state: ResMut<NextState<GameState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let Some(ctx) = ctx.try_ctx_mut() else {
+        return;
+    };
+    if show_game_errors(ctx, &game_errors) {
+        return;
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.0);
+            ui.label(RichText::new("Meadowland").font(FontId::proportional(60.0)));
+            ui.add_space(40.0);
+
+            let button_size = [200.0, 40.0];
+            if ui.add_sized(button_size, Button::new("New Game")).clicked() {
+                app_next_state.set(GameState::NewGame);
+            }
+            ui.add_enabled(false, Button::new("Load").min_size(button_size.into()))
+                .on_disabled_hover_text("Saved games are not available yet");
+            if ui.add_sized(button_size, Button::new("Options")).clicked() {
+                options_screen.open(&settings);
+            }
+            if ui.add_sized(button_size, Button::new("Quit")).clicked() {
+                app_exit_events.send(AppExit::Success);
+            }
+        });
+    });
+}
+
+/// The longest side of the map preview, in points.
+const PREVIEW_SIZE: f32 = 320.0;
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_new_game_ui(
+    mut ctx: EguiContexts,
+    biome_registry: Res<BiomeRegistry>,
+    game_world: Res<GameWorld>,
+    images: Res<Assets<Image>>,
+    map_preview: Res<MapPreview>,
+    mut new_game_settings: ResMut<NewGameSettings>,
+    mut app_next_state: ResMut<NextState<GameState>>,
+    mut preview_texture: Local<Option<(MapParameters, TextureHandle)>>,
+) {
+    let Some(ctx) = ctx.try_ctx_mut() else {
+        return;
+    };
+    let new_game_settings = &mut *new_game_settings;
+
+    if let Some((parameters, cells)) = &map_preview.map {
+        if preview_texture.as_ref().map(|(texture_parameters, _)| texture_parameters) != Some(parameters) {
+            let colors = biome_colors(&biome_registry, &game_world, &images);
+            let image = preview_image(parameters, cells, &colors);
+            let texture = ctx.load_texture("map_preview", image, TextureOptions::NEAREST);
+            *preview_texture = Some((parameters.clone(), texture));
+        }
+    }
+    let map_ready = map_preview.is_ready(&new_game_settings.map_parameters(&biome_registry));
+
+    egui::Window::new("New Game")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| ui.horizontal_top(|ui| {
+            ui.vertical(|ui| new_game_settings_ui(ui, &biome_registry, new_game_settings));
+
+            ui.separator();
+            ui.vertical(|ui| {
+                match &*preview_texture {
+                    Some((_, texture)) => {
+                        let size = texture.size_vec2();
+                        ui.image((texture.id(), size * PREVIEW_SIZE / size.max_elem()));
+                    }
+                    None => {
+                        ui.allocate_space(egui::Vec2::splat(PREVIEW_SIZE));
+                    }
+                }
+                match &map_preview.generation {
+                    Some(generation) => {
+                        ui.add(ProgressBar::new(generation.progress()).desired_width(PREVIEW_SIZE).text("Generating..."));
+                    }
+                    None => {
+                        ui.label("");
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Back").clicked() {
+                        app_next_state.set(GameState::MainMenu);
+                    }
+                    if ui.button("Reroll").clicked() {
+                        new_game_settings.seed = rand::random();
+                    }
+                    if ui.add_enabled(map_ready, Button::new("Accept")).clicked() {
+                        app_next_state.set(GameState::MapGeneration);
+                    }
+                });
+            });
+        }));
+}
+
+fn new_game_settings_ui(ui: &mut Ui, biome_registry: &BiomeRegistry, new_game_settings: &mut NewGameSettings) {
+    let map_sizes = NewGameSettings::MIN_MAP_SIZE..=NewGameSettings::MAX_MAP_SIZE;
+    Grid::new("new_game_grid").num_columns(2).spacing([20.0, 8.0]).show(ui, |ui| {
+        ui.label("Map width");
+        ui.add(Slider::new(&mut new_game_settings.width, map_sizes.clone()));
+        ui.end_row();
+
+        ui.label("Map height");
+        ui.add(Slider::new(&mut new_game_settings.height, map_sizes));
+        ui.end_row();
+
+        ui.label("Seed");
+        ui.add(DragValue::new(&mut new_game_settings.seed));
+        ui.end_row();
+
+        ui.label("Rivers");
+        ui.add(Slider::new(&mut new_game_settings.river_count, 0..=NewGameSettings::MAX_RIVER_COUNT));
+        ui.end_row();
+
+        ui.label("Villagers");
+        ui.add(Slider::new(&mut new_game_settings.pawn_count, 1..=NewGameSettings::MAX_PAWN_COUNT));
+        ui.end_row();
+    });
+
+    ui.separator();
+    ui.heading("Biome rarity");
+    Grid::new("biome_weights_grid").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+        // rivers only come from the river count, floors from mining
+        let is_generated = |biome: &Biome| !matches!(biome.kind, BiomeKind::River | BiomeKind::Floor);
+        for (_, biome) in biome_registry.iter().filter(|(_, biome)| is_generated(biome)) {
+            ui.label(&biome.name);
+            let mut weight = new_game_settings.biome_weight(&biome.name);
+            let slider = Slider::new(&mut weight, 0.0..=NewGameSettings::MAX_BIOME_WEIGHT).step_by(0.1);
+            if ui.add(slider).changed() {
+                new_game_settings.set_biome_weight(&biome.name, weight);
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// Each biome's color in the preview, the average color of its first tile texture.
+fn biome_colors(biome_registry: &BiomeRegistry, game_world: &GameWorld, images: &Assets<Image>) -> Vec<Color32> {
+    biome_registry
+        .biomes()
+        .iter()
+        .map(|biome| {
+            let (handle, _) = game_world.texture_handles_for_biome(biome).into_iter().next()?;
+            let image = images.get(&handle)?;
+            if image.texture_descriptor.format.block_copy_size(None) != Some(4) {
+                return None;
+            }
+
+            let mut sum = [0u64; 3];
+            let pixels = image.data.chunks_exact(4);
+            let count = pixels.len().max(1) as u64;
+            for pixel in pixels {
+                for (channel, value) in sum.iter_mut().zip(pixel) {
+                    *channel += *value as u64;
+                }
+            }
+            let [r, g, b] = sum.map(|channel| (channel / count) as u8);
+            Some(Color32::from_rgb(r, g, b))
+        })
+        .map(|color| color.unwrap_or(Color32::GRAY))
+        .collect()
+}
+
+/// One pixel per cell, or per few cells on maps bigger than the preview.
+fn preview_image(parameters: &MapParameters, cells: &[MapGenerationCell], colors: &[Color32]) -> ColorImage {
+    let cells_per_pixel = (parameters.width.max(parameters.height) as f32 / PREVIEW_SIZE).max(1.0);
+    let size = [
+        (parameters.width as f32 / cells_per_pixel) as usize,
+        (parameters.height as f32 / cells_per_pixel) as usize,
+    ];
+
+    let mut image = ColorImage::new(size, Color32::BLACK);
+    for row in 0..size[1] {
+        // the image's first row is the top of the map, the tilemap's first row is the bottom
+        let y = parameters.height - 1 - ((row as f32 * cells_per_pixel) as u32).min(parameters.height - 1);
+        for column in 0..size[0] {
+            let x = ((column as f32 * cells_per_pixel) as u32).min(parameters.width - 1);
+            let cell = &cells[(y * parameters.width + x) as usize];
+            image.pixels[row * size[0] + column] = colors[cell.biome_id.index()];
+        }
+    }
+    image
+}
@@ -0,0 +1,263 @@
+use crate::bundles::resource_stack_bundle::spawn_resource_stack;
+use crate::components::map_chunk::MapChunk;
+use crate::components::mine_designation::MineDesignation;
+use crate::components::pawn::Pawn;
+use crate::components::transition_overlay::TransitionOverlay;
+use crate::components::walk_path::WalkPath;
+use crate::resources::game_map::{GameMap, MapCellsChanged};
+use crate::resources::game_resources::GameResource;
+use crate::resources::input_actions::{ActionState, InputAction};
+use crate::resources::jobs::{Job, JobId, JobType, Jobs};
+use crate::resources::path_finder::PathFinder;
+use crate::resources::spatial_index::SpatialIndex;
+use crate::systems::asset_loading::LoadingAssets;
+use crate::systems::tile_textures::TilePicker;
+use crate::systems::transitions::refresh_transition_overlays;
+use crate::GameWorld;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_ecs_tilemap::tiles::{TilePos, TileStorage, TileTextureIndex};
+use bevy_egui::EguiContexts;
+
+/// Marks a rock cell for mining, or takes the mark off a cell that has one.
+#[derive(Event)]
+pub struct ToggleMineDesignation(pub UVec2);
+
+/// Toggles the mining mark on the cell under the cursor, unless the cursor is over the ui.
+pub fn designate_mining_under_cursor(
+    actions: Res<ActionState>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    game_world: Res<GameWorld>,
+    mut contexts: EguiContexts,
+    mut events: EventWriter<ToggleMineDesignation>,
+) {
+    if !actions.just_pressed(InputAction::ToggleMineDesignation) {
+        return;
+    }
+    if contexts.try_ctx_mut().is_some_and(|ctx| ctx.wants_pointer_input()) {
+        return;
+    }
+    let Some(cursor) = window_query.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    let Some(position) = camera_query
+        .iter()
+        .find_map(|(camera, camera_transform)| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    if let Some(cell) = game_world.cell_at(position) {
+        events.send(ToggleMineDesignation(cell));
+    }
+}
+
+/// Marks cells for mining and lists their jobs, only cells that can be mined take the mark.
+pub fn apply_mine_designations(
+    mut commands: Commands,
+    mut events: EventReader<ToggleMineDesignation>,
+    game_map: Res<GameMap>,
+    game_world: Res<GameWorld>,
+    asset_server: Res<AssetServer>,
+    designation_query: Query<(Entity, &MineDesignation)>,
+    mut jobs: ResMut<Jobs>,
+) {
+    let mut toggled: Vec<UVec2> = vec![];
+    for ToggleMineDesignation(cell) in events.read() {
+        // a cell toggled twice in a frame ends up how it was
+        match toggled.iter().position(|toggled_cell| toggled_cell == cell) {
+            Some(index) => _ = toggled.swap_remove(index),
+            None => toggled.push(*cell),
+        }
+    }
+
+    for cell in toggled {
+        if let Some((entity, _)) = designation_query.iter().find(|(_, designation)| designation.cell == cell) {
+            commands.entity(entity).despawn_recursive();
+            if let Some(id) = mine_job(&jobs, cell) {
+                _ = jobs.cancel(id);
+            }
+            continue;
+        }
+        if cell.x >= game_map.width() || cell.y >= game_map.height() || game_map.mined_into(cell.x, cell.y).is_none() {
+            continue;
+        }
+
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("designations/mine-overlay.png"),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(game_world.cell_size() as f32)),
+                    ..default()
+                },
+                transform: Transform::from_translation(game_world.cell_center(cell).extend(1.)),
+                ..default()
+            },
+            MineDesignation { cell, progress: 0.0 },
+            Name::new("Mine Designation"),
+        ));
+        jobs.post("Mine", JobType::Mine(cell));
+    }
+}
+
+/// Takes the marks off cells whose job was given up on after failing too often, nobody digs there anymore.
+pub fn clear_abandoned_designations(
+    mut commands: Commands,
+    jobs: Res<Jobs>,
+    designation_query: Query<(Entity, &MineDesignation)>,
+) {
+    for (entity, designation) in designation_query.iter() {
+        if mine_job(&jobs, designation.cell).is_none() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Sends the pawns that took a mining job to the cell next to the rock they get to quickest.
+pub fn walk_to_designated_rock(
+    mut commands: Commands,
+    jobs: Res<Jobs>,
+    game_world: Res<GameWorld>,
+    path_finder: Res<PathFinder>,
+    designation_query: Query<&MineDesignation>,
+    pawn_query: Query<(&Transform, Option<&WalkPath>), With<Pawn>>,
+) {
+    for designation in designation_query.iter() {
+        let cell = designation.cell;
+        let Some(holder) = mine_job(&jobs, cell).and_then(|id| jobs.get(id)).and_then(Job::holder) else {
+            continue;
+        };
+        let Ok((transform, walk_path)) = pawn_query.get(holder) else {
+            continue;
+        };
+        let Some(pawn_cell) = game_world.cell_at(transform.translation.truncate()) else {
+            continue;
+        };
+        let destination = walk_path.and_then(|path| path.0.last().copied()).unwrap_or(pawn_cell);
+        if is_next_to(destination, cell) {
+            continue;
+        }
+
+        let path = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| cell.as_ivec2() + IVec2::new(dx, dy)))
+            .filter(|neighbor| *neighbor != cell.as_ivec2() && neighbor.cmpge(IVec2::ZERO).all())
+            .filter_map(|neighbor| path_finder.find_path(pawn_cell, neighbor.as_uvec2()))
+            .min_by(|a, b| a.cost.total_cmp(&b.cost));
+        if let Some(path) = path {
+            commands.entity(holder).insert(WalkPath(path.cells[1..].to_vec()));
+        }
+    }
+}
+
+/// The pawn that took a marked cell's job digs at it once it stands next to it. Once a cell took its
+/// biome's mining work, it turns into the biome it's mined into and drops the biome's resources.
+#[allow(clippy::too_many_arguments)]
+pub fn dig_designated_rock(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut game_map: ResMut<GameMap>,
+    game_world: Res<GameWorld>,
+    asset_server: Res<AssetServer>,
+    loading_assets: Res<LoadingAssets>,
+    game_resources: Res<Assets<GameResource>>,
+    pawn_query: Query<&Transform, (With<Pawn>, Without<WalkPath>)>,
+    mut designation_query: Query<(Entity, &mut MineDesignation)>,
+    chunk_query: Query<(&MapChunk, &TileStorage)>,
+    mut tile_query: Query<&mut TileTextureIndex>,
+    overlay_query: Query<(Entity, &TileStorage, &TransitionOverlay)>,
+    mut images: ResMut<Assets<Image>>,
+    mut jobs: ResMut<Jobs>,
+    mut spatial_index: ResMut<SpatialIndex>,
+    mut changed_events: EventWriter<MapCellsChanged>,
+) {
+    let mut mined = vec![];
+    for (entity, mut designation) in designation_query.iter_mut() {
+        let cell = designation.cell;
+        let job = mine_job(&jobs, cell).and_then(|id| jobs.get(id));
+        let Some((id, holder)) = job.and_then(|job| Some((job.id(), job.holder()?))) else {
+            continue;
+        };
+        let digging = pawn_query
+            .get(holder)
+            .ok()
+            .and_then(|transform| game_world.cell_at(transform.translation.truncate()))
+            .is_some_and(|pawn_cell| is_next_to(pawn_cell, cell));
+        if !digging {
+            continue;
+        }
+        designation.progress += time.delta_seconds();
+        _ = jobs.start(id, holder);
+
+        let Some(mining) = game_map.biome_at(cell.x, cell.y).mining.clone() else {
+            continue;
+        };
+        if designation.progress < mining.work {
+            continue;
+        }
+        let Some(mined_into) = game_map.mined_into(cell.x, cell.y) else {
+            continue;
+        };
+
+        game_map.set_biome(cell.x, cell.y, mined_into);
+        commands.entity(entity).despawn_recursive();
+        if let Some(id) = mine_job(&jobs, cell) {
+            _ = jobs.finish(id);
+        }
+        for drop in &mining.drops {
+            let definition_path = loading_assets
+                .in_load_order(&game_resources)
+                .filter(|(_, game_resource)| game_resource.name == drop.resource)
+                .map(|(path, _)| path)
+                .last();
+            let Some(path) = definition_path else {
+                warn!("Mined out a cell dropping {}, but no resource is named that", drop.resource);
+                continue;
+            };
+            let spawned = spawn_resource_stack(
+                &mut commands,
+                &game_world,
+                &asset_server,
+                &game_map,
+                &mut spatial_index,
+                path,
+                &drop.resource,
+                drop.amount,
+                cell,
+            );
+            if let Err(error) = spawned {
+                warn!("Mined out a cell, but couldn't drop its {}: {error}", drop.resource);
+            }
+        }
+        mined.push(cell);
+    }
+    if mined.is_empty() {
+        return;
+    }
+
+    let tile_picker = TilePicker::new(&game_world, game_map.biomes(), game_map.seed());
+    for cell in &mined {
+        let origin = *cell / MapChunk::SIZE * MapChunk::SIZE;
+        let Some((chunk, tile_storage)) = chunk_query.iter().find(|(chunk, _)| chunk.origin == origin) else {
+            continue;
+        };
+        let relative = *cell - chunk.origin;
+        let tile = tile_storage.get(&TilePos { x: relative.x, y: relative.y });
+        let Some(mut texture_index) = tile.and_then(|tile| tile_query.get_mut(tile).ok()) else {
+            continue;
+        };
+        if let Some(index) = tile_picker.pick(game_map.biome_id_at(cell.x, cell.y), cell.x, cell.y) {
+            texture_index.0 = index;
+        }
+    }
+    refresh_transition_overlays(&mut commands, &game_map, &game_world, &mut images, &overlay_query, &mined);
+    changed_events.send(MapCellsChanged(mined));
+}
+
+fn mine_job(jobs: &Jobs, cell: UVec2) -> Option<JobId> {
+    jobs.find(&JobType::Mine(cell)).map(Job::id)
+}
+
+fn is_next_to(pawn_cell: UVec2, cell: UVec2) -> bool {
+    pawn_cell != cell && pawn_cell.as_ivec2().distance_squared(cell.as_ivec2()) <= 2
+}
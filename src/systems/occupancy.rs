@@ -0,0 +1,113 @@
+use crate::components::obstruction::Obstruction;
+use crate::components::plant::Plant;
+use crate::components::tile_occupant::TileOccupant;
+use crate::resources::biomes::BiomeKind;
+use crate::resources::game_map::{GameMap, MapCellsChanged};
+use crate::resources::plants::PlantDefinition;
+use crate::resources::spatial_index::SpatialIndex;
+use crate::systems::asset_loading::LoadingAssets;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+/// Whether something of a kind may be placed on `cell`, the reason it may not otherwise.
+pub fn check_placement(game_map: &GameMap, spatial_index: &SpatialIndex, cell: UVec2, occupant: TileOccupant) -> Result<(), String> {
+    if cell.x >= game_map.width() || cell.y >= game_map.height() {
+        return Err(format!("Cell [{}, {}] is off the map", cell.x, cell.y));
+    }
+    let biome = game_map.biome_at(cell.x, cell.y);
+    match occupant {
+        // they need ground to take root or to stand on
+        TileOccupant::Plant | TileOccupant::Building if !matches!(biome.kind, BiomeKind::Land | BiomeKind::Floor) => {
+            return Err(format!("Can't place {} on {}", occupant.label(), biome.name));
+        }
+        TileOccupant::Pawn | TileOccupant::Item if game_map.movement_modifier(cell.x, cell.y) <= 0.0 => {
+            return Err(format!("Can't place {} on [{}, {}], nothing gets there", occupant.label(), cell.x, cell.y));
+        }
+        _ => {}
+    }
+    if let Some((_, other)) = spatial_index.all_at(cell).find(|(_, other)| !occupant.can_share_cell_with(*other)) {
+        return Err(format!("Can't place {} on [{}, {}], {} is there", occupant.label(), cell.x, cell.y, other.label()));
+    }
+    Ok(())
+}
+
+/// The cell nearest to `cell` something of a kind may be placed on, for spawns that don't need an exact spot.
+pub fn nearest_placement(game_map: &GameMap, spatial_index: &SpatialIndex, cell: UVec2, occupant: TileOccupant) -> Option<UVec2> {
+    let size = UVec2::new(game_map.width(), game_map.height()).as_ivec2();
+    let center = cell.as_ivec2();
+    (0..size.max_element()).find_map(|ring| {
+        let mut candidates: Vec<_> = (-ring..=ring)
+            .flat_map(|dy| (-ring..=ring).map(move |dx| IVec2::new(dx, dy)))
+            .filter(|offset| offset.abs().max_element() == ring)
+            .map(|offset| center + offset)
+            .filter(|candidate| candidate.cmpge(IVec2::ZERO).all() && candidate.cmplt(size).all())
+            .collect();
+        candidates.sort_by_key(|candidate| (candidate.distance_squared(center), candidate.y, candidate.x));
+        candidates
+            .into_iter()
+            .map(|candidate| candidate.as_uvec2())
+            .find(|candidate| check_placement(game_map, spatial_index, *candidate, occupant).is_ok())
+    })
+}
+
+pub fn attach_plant_obstructions(
+    mut commands: Commands,
+    plant_query: Query<(Entity, &Plant), Added<Plant>>,
+    plant_definitions: Res<Assets<PlantDefinition>>,
+    loading_assets: Res<LoadingAssets>,
+) {
+    for (entity, plant) in plant_query.iter() {
+        let movement_modifier = loading_assets
+            .definition(&plant_definitions, &plant.name)
+            .map_or(1.0, |definition| definition.movement_modifier);
+
+        if movement_modifier < 1.0 {
+            commands.entity(entity).insert(Obstruction { movement_modifier });
+        }
+    }
+}
+
+/// Things in the way that were added, changed or moved.
+type ObstructionChanged = (With<Obstruction>, Or<(Changed<Obstruction>, Changed<Transform>)>);
+
+/// Works the obstruction of the cells out again as the things in the way come, move and go, the
+/// obstructions of the things on a cell add up.
+pub fn update_obstructions(
+    mut game_map: ResMut<GameMap>,
+    spatial_index: Res<SpatialIndex>,
+    obstruction_query: Query<&Obstruction>,
+    changed_query: Query<Entity, ObstructionChanged>,
+    mut removed_obstructions: RemovedComponents<Obstruction>,
+    mut obstructed_cells: Local<HashMap<Entity, UVec2>>,
+    mut changed_events: EventWriter<MapCellsChanged>,
+) {
+    let mut touched = HashSet::new();
+    for entity in removed_obstructions.read() {
+        touched.extend(obstructed_cells.remove(&entity));
+    }
+    for entity in changed_query.iter() {
+        let cell = spatial_index.cell_of(entity);
+        let previous = match cell {
+            Some(cell) => obstructed_cells.insert(entity, cell),
+            None => obstructed_cells.remove(&entity),
+        };
+        touched.extend(previous);
+        touched.extend(cell);
+    }
+
+    let mut changed: Vec<_> = touched
+        .into_iter()
+        .filter(|cell| {
+            let obstruction = spatial_index
+                .all_at(*cell)
+                .filter_map(|(entity, _)| obstruction_query.get(entity).ok())
+                .map(|obstruction| obstruction.movement_modifier)
+                .product();
+            game_map.set_obstruction(cell.x, cell.y, obstruction)
+        })
+        .collect();
+    if !changed.is_empty() {
+        changed.sort_by_key(|cell| (cell.y, cell.x));
+        changed_events.send(MapCellsChanged(changed));
+    }
+}
@@ -0,0 +1,19 @@
+use crate::resources::game_map::{GameMap, MapCellsChanged};
+use crate::resources::path_finder::PathFinder;
+use bevy::prelude::*;
+
+pub fn setup_path_finder(mut commands: Commands, game_map: Res<GameMap>) {
+    commands.insert_resource(PathFinder::new(&game_map));
+}
+
+/// Works out the clusters around changed cells again, all the cells changed in a frame at once.
+pub fn update_path_finder(
+    mut events: EventReader<MapCellsChanged>,
+    game_map: Res<GameMap>,
+    mut path_finder: ResMut<PathFinder>,
+) {
+    let cells: Vec<_> = events.read().flat_map(|MapCellsChanged(cells)| cells.iter().copied()).collect();
+    if !cells.is_empty() {
+        path_finder.update_cells(&game_map, &cells);
+    }
+}
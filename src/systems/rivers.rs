@@ -0,0 +1,227 @@
+use crate::resources::biome_registry::BiomeId;
+use crate::resources::biomes::{Biome, BiomeKind};
+use crate::systems::map_generation::{MapGenerationCell, MapParameters};
+use bevy::utils::HashSet;
+use noise::{NoiseFn, OpenSimplex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// How rivers are generated, part of `MapParameters`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiverParameters {
+    /// How many rivers to start, fewer end up on maps without enough high ground.
+    pub count: u32,
+    /// The lowest height a river can start at.
+    pub min_source_height: f64,
+    /// How many cells a river has to drain for every cell it grows wider on each side.
+    pub widening_flow: u32,
+    /// The most cells a river is wide on each side of its middle.
+    pub max_half_width: u32,
+    /// How much fine noise bends the rivers, the height noise alone is so smooth that they'd run straight.
+    pub meander: f64,
+}
+
+impl Default for RiverParameters {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            min_source_height: 0.6,
+            widening_flow: 1500,
+            max_half_width: 2,
+            meander: 0.02,
+        }
+    }
+}
+
+/// Rivers pick their sources from the map's seed too, this keeps those picks apart from the noise's.
+const RIVER_SEED: u64 = 0x5249_5645_5253;
+
+const MEANDER_FREQUENCY: f64 = 0.1;
+
+const NEIGHBORS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Runs rivers from random high cells down to the water and stamps them into `cells` with the table's
+/// river biome. Does nothing when the table has no river biome.
+///
+/// Every cell drains towards the water or the edge of the map, and how many cells drain through a cell
+/// is its flow. Rivers follow the drainage, widening as the flow grows.
+pub fn carve_rivers(cells: &mut [MapGenerationCell], biomes: &[Biome], parameters: &MapParameters) {
+    let rivers = &parameters.rivers;
+    let Some(river_index) = biomes.iter().position(|biome| biome.kind == BiomeKind::River) else {
+        return;
+    };
+    if rivers.count == 0 {
+        return;
+    }
+    let river_id = BiomeId(river_index as u16);
+    let grid = Grid { width: parameters.width as i32, height: parameters.height as i32 };
+    let is_water = |cell: &MapGenerationCell| biomes[cell.biome_id.index()].kind == BiomeKind::Water;
+
+    let terrain = meandering_terrain(cells, &grid, rivers.meander, parameters.seed);
+    let drainage = Drainage::new(&terrain, &grid, |index| is_water(&cells[index]));
+    let flow = drainage.flow();
+
+    let candidates: Vec<_> = (0..cells.len())
+        .filter(|index| !grid.is_edge(*index) && !is_water(&cells[*index]))
+        .filter(|index| cells[*index].height >= rivers.min_source_height)
+        .collect();
+    let mut rng = StdRng::seed_from_u64(parameters.seed ^ RIVER_SEED);
+    let sources: Vec<_> = candidates.choose_multiple(&mut rng, rivers.count as usize).copied().collect();
+
+    for source in sources {
+        let mut stamped = HashSet::new();
+        let mut current = source;
+        loop {
+            let joined_river = cells[current].biome_id == river_id && !stamped.contains(&current);
+            if is_water(&cells[current]) || joined_river {
+                break;
+            }
+
+            let half_width = (flow[current] / rivers.widening_flow.max(1)).min(rivers.max_half_width) as i32;
+            for index in grid.disc(current, half_width) {
+                if !is_water(&cells[index]) && cells[index].biome_id != river_id {
+                    cells[index].biome_id = river_id;
+                    cells[index].matched = true;
+                    stamped.insert(index);
+                }
+            }
+
+            // on the edge of the map the river flows off it
+            match drainage.downstream[current] {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+    }
+}
+
+fn meandering_terrain(cells: &[MapGenerationCell], grid: &Grid, meander: f64, seed: u64) -> Vec<f64> {
+    let noise = OpenSimplex::new(StdRng::seed_from_u64(seed ^ RIVER_SEED).gen());
+    (0..cells.len())
+        .map(|index| {
+            let (x, y) = grid.position(index);
+            let bend = noise.get([x as f64 * MEANDER_FREQUENCY, y as f64 * MEANDER_FREQUENCY]);
+            cells[index].height + bend * meander
+        })
+        .collect()
+}
+
+/// Where every cell drains to, found by flooding the map upwards from its outlets, the water and the edges.
+/// Dips fill up before the flood spills over their rim, so every cell has a way out.
+struct Drainage {
+    /// The neighbor each cell drains into, `None` for the outlets.
+    downstream: Vec<Option<usize>>,
+    /// Every cell, each one after the cell it drains into.
+    order: Vec<usize>,
+}
+
+impl Drainage {
+    fn new(terrain: &[f64], grid: &Grid, is_outlet: impl Fn(usize) -> bool) -> Self {
+        let mut downstream = vec![None; terrain.len()];
+        let mut order = Vec::with_capacity(terrain.len());
+        let mut reached = vec![false; terrain.len()];
+        let mut frontier = BinaryHeap::new();
+
+        for index in 0..terrain.len() {
+            if is_outlet(index) || grid.is_edge(index) {
+                reached[index] = true;
+                frontier.push(Flooded { level: terrain[index], terrain: terrain[index], index });
+            }
+        }
+
+        while let Some(Flooded { level, index, .. }) = frontier.pop() {
+            order.push(index);
+            for neighbor in grid.neighbors(index) {
+                if reached[neighbor] {
+                    continue;
+                }
+                reached[neighbor] = true;
+                downstream[neighbor] = Some(index);
+                let level = terrain[neighbor].max(level);
+                frontier.push(Flooded { level, terrain: terrain[neighbor], index: neighbor });
+            }
+        }
+
+        Self { downstream, order }
+    }
+
+    /// How many cells drain through each cell, itself included.
+    fn flow(&self) -> Vec<u32> {
+        let mut flow = vec![1u32; self.downstream.len()];
+        for index in self.order.iter().rev() {
+            if let Some(next) = self.downstream[*index] {
+                flow[next] += flow[*index];
+            }
+        }
+        flow
+    }
+}
+
+/// A cell the flood reached, the heap hands out the lowest level first. Across a filled dip, where the
+/// level is the same everywhere, it follows the dip's bottom instead of running in straight lines.
+struct Flooded {
+    level: f64,
+    terrain: f64,
+    index: usize,
+}
+
+impl Ord for Flooded {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.level
+            .total_cmp(&self.level)
+            .then(other.terrain.total_cmp(&self.terrain))
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Flooded {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Flooded {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Flooded {}
+
+struct Grid {
+    width: i32,
+    height: i32,
+}
+
+impl Grid {
+    fn position(&self, index: usize) -> (i32, i32) {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.position(index);
+        NEIGHBORS.iter().filter_map(move |(dx, dy)| self.index(x + dx, y + dy))
+    }
+
+    fn is_edge(&self, index: usize) -> bool {
+        let (x, y) = self.position(index);
+        x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let inside = (0..self.width).contains(&x) && (0..self.height).contains(&y);
+        inside.then(|| (y * self.width + x) as usize)
+    }
+
+    /// The cells within `radius` of the cell, the cell itself for a radius of 0.
+    fn disc(&self, index: usize, radius: i32) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.position(index);
+        (-radius..=radius)
+            .flat_map(move |dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(move |(dx, dy)| dx * dx + dy * dy <= radius * radius + radius)
+            .filter_map(move |(dx, dy)| self.index(x + dx, y + dy))
+    }
+}
@@ -0,0 +1,224 @@
+use crate::resources::input_actions::{ActionState, InputAction, InputBinding, Modifiers};
+use crate::resources::settings::{Settings, WindowModeSetting};
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::egui::{self, Align2, ComboBox, DragValue, Grid, Slider};
+use bevy_egui::{EguiContexts, EguiSettings};
+use bevy_framepace::FramepaceSettings;
+
+/// The options screen, the settings are edited in `draft` until they're applied.
+#[derive(Resource, Default)]
+pub struct OptionsScreen {
+    open: bool,
+    draft: Option<Settings>,
+    /// The action waiting for the player to press a new binding, and the slot of the binding it replaces.
+    rebinding: Option<(InputAction, usize)>,
+    status: Option<String>,
+}
+
+impl OptionsScreen {
+    pub fn open(&mut self, settings: &Settings) {
+        self.open = true;
+        self.draft = Some(settings.clone());
+        self.status = None;
+    }
+
+    /// Waits for the next key or mouse button pressed to bind it to `action` in place of the binding at `slot`.
+    pub fn start_rebinding(&mut self, action: InputAction, slot: usize) {
+        self.rebinding = Some((action, slot));
+    }
+
+    pub fn is_rebinding(&self) -> bool {
+        self.rebinding.is_some()
+    }
+}
+
+/// Applies changed settings to the primary window and the frame limiter.
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    framepace_settings: Option<ResMut<FramepaceSettings>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut window in window_query.iter_mut() {
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+        window.resolution.set(settings.resolution.width as f32, settings.resolution.height as f32);
+    }
+    if let Some(mut framepace_settings) = framepace_settings {
+        framepace_settings.limiter = settings.frame_limiter();
+    }
+}
+
+/// egui adds its settings to a window some time after the window shows up, so the scale is kept in sync every frame.
+pub fn apply_ui_scale(settings: Res<Settings>, mut egui_settings_query: Query<&mut EguiSettings>) {
+    for mut egui_settings in egui_settings_query.iter_mut() {
+        if egui_settings.scale_factor != settings.ui_scale {
+            egui_settings.scale_factor = settings.ui_scale;
+        }
+    }
+}
+
+pub fn toggle_options_screen(
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    mut options_screen: ResMut<OptionsScreen>,
+) {
+    if options_screen.rebinding.is_some() {
+        return;
+    }
+
+    if actions.just_pressed(InputAction::ToggleOptions) {
+        options_screen.open = !options_screen.open;
+        options_screen.draft = options_screen.open.then(|| settings.clone());
+        options_screen.status = None;
+    }
+}
+
+/// Binds the first key or mouse button pressed, with the modifiers held, to the action being rebound in
+/// place of the binding picked, the action's other bindings stay.
+pub fn capture_rebinding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut options_screen: ResMut<OptionsScreen>,
+) {
+    let options_screen = &mut *options_screen;
+    let (Some((action, slot)), Some(draft)) = (options_screen.rebinding, options_screen.draft.as_mut()) else {
+        return;
+    };
+
+    let key = keys.get_just_pressed().find(|key| !Modifiers::is_modifier_key(**key));
+    let binding = match (key, mouse_buttons.get_just_pressed().next()) {
+        (Some(key), _) => InputBinding::key(*key),
+        (None, Some(button)) => InputBinding::mouse(*button),
+        (None, None) => return,
+    };
+    let binding = binding.with_modifiers(Modifiers::held(&keys));
+
+    let unbound = draft.keybindings.bind(action, slot, binding);
+    options_screen.status = (!unbound.is_empty()).then(|| {
+        let labels: Vec<_> = unbound.iter().map(|action| action.label()).collect();
+        format!("{binding} was taken from {}", labels.join(", "))
+    });
+    options_screen.rebinding = None;
+}
+
+pub fn run_options_ui(
+    mut ctx: EguiContexts,
+    mut settings: ResMut<Settings>,
+    mut options_screen: ResMut<OptionsScreen>,
+) {
+    let Some(ctx) = ctx.try_ctx_mut() else {
+        return;
+    };
+    let options_screen = &mut *options_screen;
+    let Some(draft) = options_screen.draft.as_mut() else {
+        return;
+    };
+
+    let mut open = options_screen.open;
+    let mut close = false;
+    egui::Window::new("Options")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            Grid::new("options_grid").num_columns(2).spacing([20.0, 8.0]).show(ui, |ui| {
+                ui.label("Window mode");
+                ComboBox::from_id_salt("window_mode")
+                    .selected_text(format!("{:?}", draft.window_mode))
+                    .show_ui(ui, |ui| {
+                        for window_mode in [
+                            WindowModeSetting::Windowed,
+                            WindowModeSetting::BorderlessFullscreen,
+                            WindowModeSetting::Fullscreen,
+                        ] {
+                            ui.selectable_value(&mut draft.window_mode, window_mode, format!("{window_mode:?}"));
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Resolution");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut draft.resolution.width).range(640..=7680));
+                    ui.label("x");
+                    ui.add(DragValue::new(&mut draft.resolution.height).range(480..=4320));
+                });
+                ui.end_row();
+
+                ui.label("VSync");
+                ui.checkbox(&mut draft.vsync, "");
+                ui.end_row();
+
+                ui.label("Frame cap");
+                ui.horizontal(|ui| {
+                    let mut capped = draft.frame_cap.is_some();
+                    ui.checkbox(&mut capped, "");
+                    let mut frame_cap = draft.frame_cap.unwrap_or(60);
+                    ui.add_enabled(capped, Slider::new(&mut frame_cap, 30..=240).suffix(" FPS"));
+                    draft.frame_cap = capped.then_some(frame_cap);
+                });
+                ui.end_row();
+
+                ui.label("UI scale");
+                ui.add(Slider::new(&mut draft.ui_scale, Settings::MIN_UI_SCALE..=Settings::MAX_UI_SCALE).step_by(0.05));
+                ui.end_row();
+
+            });
+
+            ui.separator();
+            ui.heading("Controls");
+            Grid::new("controls_grid").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                for action in InputAction::ALL {
+                    ui.label(action.label());
+                    // a button for every binding to replace it, and one more to add another
+                    ui.horizontal(|ui| {
+                        let bindings = draft.keybindings.bindings(action);
+                        if bindings.is_empty() {
+                            ui.label("Unbound");
+                        }
+                        for slot in 0..=bindings.len() {
+                            let text = match (options_screen.rebinding == Some((action, slot)), bindings.get(slot)) {
+                                (true, _) => "Press a key...".to_string(),
+                                (false, Some(binding)) => binding.to_string(),
+                                (false, None) => "+".to_string(),
+                            };
+                            if ui.button(text).clicked() {
+                                options_screen.rebinding = Some((action, slot));
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    *settings = draft.clone();
+                    options_screen.status = Some(match settings.save() {
+                        Ok(()) => "Saved".to_string(),
+                        Err(error) => error,
+                    });
+                }
+                if ui.button("Defaults").clicked() {
+                    *draft = Settings::default();
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+            if let Some(status) = &options_screen.status {
+                ui.label(status);
+            }
+        });
+
+    if !open || close {
+        options_screen.open = false;
+        options_screen.draft = None;
+        options_screen.rebinding = None;
+    }
+}
@@ -0,0 +1,25 @@
+use crate::components::tile_occupant::TileOccupant;
+use crate::resources::spatial_index::SpatialIndex;
+use crate::GameWorld;
+use bevy::prelude::*;
+
+/// Occupants that were spawned, moved or changed what they are.
+type OccupantChanged = Or<(Changed<Transform>, Changed<TileOccupant>)>;
+
+/// Keeps the `SpatialIndex` up with the entities on the map as they're spawned, moved and despawned.
+pub fn update_spatial_index(
+    game_world: Res<GameWorld>,
+    mut spatial_index: ResMut<SpatialIndex>,
+    occupant_query: Query<(Entity, &Transform, &TileOccupant), OccupantChanged>,
+    mut removed_occupants: RemovedComponents<TileOccupant>,
+) {
+    for entity in removed_occupants.read() {
+        spatial_index.remove(entity);
+    }
+    for (entity, transform, occupant) in occupant_query.iter() {
+        match game_world.cell_at(transform.translation.truncate()) {
+            Some(cell) => spatial_index.insert(entity, cell, *occupant),
+            None => spatial_index.remove(entity),
+        }
+    }
+}
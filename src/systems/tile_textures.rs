@@ -0,0 +1,149 @@
+use crate::resources::biome_registry::BiomeId;
+use crate::resources::biomes::Biome;
+use crate::GameWorld;
+use bevy::prelude::*;
+use noise::{NoiseFn, OpenSimplex};
+
+/// Patches are where the patch noise rises past the first level, fading in until the second.
+const PATCH_EDGES: (f64, f64) = (0.3, 0.4);
+/// How much of the map the patches cover with those edges. A texture in patches weighs its weight divided
+/// by this inside of them, so its weight is still what it averages to over the map.
+const PATCH_COVERAGE: f64 = 0.063;
+
+/// Picks the tile texture of every cell from its biome's textures, by their `tileWeights`. A pick
+/// only depends on the map's seed and the cell, so the same map always gets the same tiles.
+pub struct TilePicker {
+    seed: u64,
+    biomes: Vec<BiomeTextures>,
+}
+
+struct BiomeTextures {
+    textures: Vec<WeightedTexture>,
+    /// The noise and frequency of every tile weight with a patch size, by the index of the tile weight.
+    patches: Vec<Option<(OpenSimplex, f64)>>,
+}
+
+struct WeightedTexture {
+    /// The texture's index in `GameWorld::texture_handles_for_all_biomes`.
+    index: u32,
+    weight: f64,
+    /// The tile weight the texture's weight comes from.
+    tile_weight: Option<usize>,
+}
+
+impl TilePicker {
+    /// `biomes` are indexed by their id, like `GameMap::biomes`.
+    pub fn new(game_world: &GameWorld, biomes: &[Biome], seed: u64) -> Self {
+        let biomes = biomes
+            .iter()
+            .enumerate()
+            .map(|(biome_index, biome)| {
+                let textures = game_world
+                    .texture_handles_for_biome(biome)
+                    .into_iter()
+                    .map(|(handle, index)| {
+                        let name = texture_name(&handle);
+                        let tile_weight = biome.tile_weights.iter().position(|tile_weight| tile_weight.matches(&name));
+                        let weight = tile_weight.map_or(1.0, |tile_weight| biome.tile_weights[tile_weight].weight);
+                        WeightedTexture { index, weight, tile_weight }
+                    })
+                    .collect();
+                let patches = biome
+                    .tile_weights
+                    .iter()
+                    .enumerate()
+                    .map(|(tile_weight_index, tile_weight)| {
+                        let noise_seed = cell_hash(seed, biome_index as u32, tile_weight_index as u32) as u32;
+                        Some((OpenSimplex::new(noise_seed), 1.0 / tile_weight.patch_size?))
+                    })
+                    .collect();
+                BiomeTextures { textures, patches }
+            })
+            .collect();
+
+        Self { seed, biomes }
+    }
+
+    /// The texture index for the cell at `x`, `y`, `None` when its biome has no textures. When every
+    /// texture weighs nothing there, any of them can be picked.
+    pub fn pick(&self, biome_id: BiomeId, x: u32, y: u32) -> Option<u32> {
+        let biome = &self.biomes[biome_id.index()];
+        let total: f64 = biome.textures.iter().map(|texture| biome.weight_at(texture, x, y)).sum();
+        let roll = (cell_hash(self.seed, x, y) >> 11) as f64 / (1u64 << 53) as f64;
+        if total <= 0.0 {
+            let index = (roll * biome.textures.len() as f64) as usize;
+            return biome.textures.get(index).map(|texture| texture.index);
+        }
+
+        let mut remaining = roll * total;
+        for texture in &biome.textures {
+            remaining -= biome.weight_at(texture, x, y);
+            if remaining < 0.0 {
+                return Some(texture.index);
+            }
+        }
+        biome.textures.last().map(|texture| texture.index)
+    }
+
+    /// The texture picked most often for the biome, the heaviest one and the first on ties.
+    pub fn main_texture(&self, biome_id: BiomeId) -> Option<u32> {
+        let textures = &self.biomes[biome_id.index()].textures;
+        let heaviest = textures.iter().map(|texture| texture.weight).fold(f64::MIN, f64::max);
+        textures.iter().find(|texture| texture.weight == heaviest).map(|texture| texture.index)
+    }
+}
+
+impl BiomeTextures {
+    fn weight_at(&self, texture: &WeightedTexture, x: u32, y: u32) -> f64 {
+        let Some((noise, frequency)) = texture.tile_weight.and_then(|tile_weight| self.patches[tile_weight].as_ref()) else {
+            return texture.weight;
+        };
+        let (start, full) = PATCH_EDGES;
+        let patch = ((noise.get([x as f64 * frequency, y as f64 * frequency]) - start) / (full - start)).clamp(0.0, 1.0);
+        texture.weight * patch * patch * (3.0 - 2.0 * patch) / PATCH_COVERAGE
+    }
+}
+
+/// An error for every tile weight of `biomes` that matches none of its biome's textures, a typo in one would
+/// otherwise leave every texture at a weight of 1.
+pub fn unmatched_tile_weights(game_world: &GameWorld, biomes: &[Biome]) -> Vec<String> {
+    biomes
+        .iter()
+        .flat_map(|biome| {
+            let texture_names: Vec<_> = game_world
+                .texture_handles_for_biome(biome)
+                .iter()
+                .map(|(handle, _)| texture_name(handle))
+                .collect();
+            biome
+                .tile_weights
+                .iter()
+                .filter(move |tile_weight| !texture_names.iter().any(|name| tile_weight.matches(name)))
+                .map(move |tile_weight| {
+                    format!(
+                        "Biome {} weighs {}, but no texture in biomes/{} matches it",
+                        biome.name,
+                        tile_weight.texture,
+                        biome.tile_name()
+                    )
+                })
+        })
+        .collect()
+}
+
+/// The texture's file name without its extension, which tile weights match against.
+pub fn texture_name(handle: &Handle<Image>) -> String {
+    handle
+        .path()
+        .and_then(|path| path.path().file_stem())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Mixes the seed and two numbers into well spread bits, with the SplitMix64 finalizer.
+fn cell_hash(seed: u64, a: u32, b: u32) -> u64 {
+    let mut hash = seed ^ ((a as u64) << 32 | b as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
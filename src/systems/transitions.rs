@@ -0,0 +1,305 @@
+use crate::components::map_chunk::MapChunk;
+use crate::components::transition_overlay::TransitionOverlay;
+use crate::resources::biome_registry::BiomeId;
+use crate::resources::biomes::Biome;
+use crate::resources::game_map::GameMap;
+use crate::systems::map_generation::{chunk_tilemap, MAP_Z};
+use crate::systems::tile_textures::TilePicker;
+use crate::GameWorld;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::map::{TilemapId, TilemapTexture};
+use bevy_ecs_tilemap::tiles::{TileBundle, TilePos, TileStorage, TileTextureIndex};
+use std::ops::BitOr;
+
+/// How far apart the overlay layers are drawn, they all stay below anything standing on the map.
+const OVERLAY_LAYER_Z: f32 = 0.1;
+
+/// Which of a cell's neighbors a biome blends in from, one bit for each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EdgeMask(pub u8);
+
+impl EdgeMask {
+    pub const NORTH: EdgeMask = EdgeMask(1);
+    pub const EAST: EdgeMask = EdgeMask(1 << 1);
+    pub const SOUTH: EdgeMask = EdgeMask(1 << 2);
+    pub const WEST: EdgeMask = EdgeMask(1 << 3);
+    pub const NORTH_EAST: EdgeMask = EdgeMask(1 << 4);
+    pub const SOUTH_EAST: EdgeMask = EdgeMask(1 << 5);
+    pub const SOUTH_WEST: EdgeMask = EdgeMask(1 << 6);
+    pub const NORTH_WEST: EdgeMask = EdgeMask(1 << 7);
+
+    /// Every neighbor with its offset on the map, north is up.
+    const NEIGHBORS: [(EdgeMask, i32, i32); 8] = [
+        (Self::NORTH, 0, 1),
+        (Self::EAST, 1, 0),
+        (Self::SOUTH, 0, -1),
+        (Self::WEST, -1, 0),
+        (Self::NORTH_EAST, 1, 1),
+        (Self::SOUTH_EAST, 1, -1),
+        (Self::SOUTH_WEST, -1, -1),
+        (Self::NORTH_WEST, -1, 1),
+    ];
+
+    pub fn contains(self, other: EdgeMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Drops the corners next to a side that's in the mask, the side's blend covers them already.
+    /// Masks that look the same come down to one, so fewer textures get blended.
+    pub fn reduced(self) -> EdgeMask {
+        let mut mask = self;
+        for (corner, sides) in [
+            (Self::NORTH_EAST, Self::NORTH | Self::EAST),
+            (Self::SOUTH_EAST, Self::SOUTH | Self::EAST),
+            (Self::SOUTH_WEST, Self::SOUTH | Self::WEST),
+            (Self::NORTH_WEST, Self::NORTH | Self::WEST),
+        ] {
+            if self.0 & sides.0 != 0 {
+                mask.0 &= !corner.0;
+            }
+        }
+        mask
+    }
+}
+
+impl BitOr for EdgeMask {
+    type Output = EdgeMask;
+
+    fn bitor(self, other: EdgeMask) -> EdgeMask {
+        EdgeMask(self.0 | other.0)
+    }
+}
+
+/// A neighboring biome blending over the edges of a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub biome_id: BiomeId,
+    pub mask: EdgeMask,
+}
+
+/// Whether `over`'s tiles blend over the edges of `under`'s, see `BiomeTransitions::layer`.
+/// Biomes sharing their tiles never blend, it wouldn't show.
+pub fn blends_over(over: &Biome, under: &Biome) -> bool {
+    if over.tile_name() == under.tile_name() {
+        return false;
+    }
+    match (&over.transitions, &under.transitions) {
+        (Some(over), Some(under)) => over.layer > under.layer,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// The neighboring biomes blending over the cell, the lowest layer first so the higher ones are drawn on top.
+pub fn transitions_at(game_map: &GameMap, x: u32, y: u32) -> Vec<Transition> {
+    let biomes = game_map.biomes();
+    let biome = game_map.biome_at(x, y);
+    let mut transitions: Vec<Transition> = vec![];
+
+    for (side, dx, dy) in EdgeMask::NEIGHBORS {
+        let (neighbor_x, neighbor_y) = (x as i32 + dx, y as i32 + dy);
+        if !(0..game_map.width() as i32).contains(&neighbor_x) || !(0..game_map.height() as i32).contains(&neighbor_y) {
+            continue;
+        }
+        let neighbor_id = game_map.biome_id_at(neighbor_x as u32, neighbor_y as u32);
+        if !blends_over(&biomes[neighbor_id.index()], biome) {
+            continue;
+        }
+        match transitions.iter_mut().find(|transition| transition.biome_id == neighbor_id) {
+            Some(transition) => transition.mask = transition.mask | side,
+            None => transitions.push(Transition { biome_id: neighbor_id, mask: side }),
+        }
+    }
+
+    for transition in &mut transitions {
+        transition.mask = transition.mask.reduced();
+    }
+    transitions.sort_by_key(|transition| {
+        let layer = biomes[transition.biome_id.index()].transitions.as_ref().map(|transitions| transitions.layer);
+        (layer, transition.biome_id)
+    });
+    transitions
+}
+
+/// A copy of a tile texture that fades out away from the sides and corners in `mask`, over `reach` of
+/// the tile. Only 8 bit RGBA textures can be blended, `None` for any other.
+pub fn blend_edges(texture: &Image, mask: EdgeMask, reach: f32) -> Option<Image> {
+    if !matches!(texture.texture_descriptor.format, TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm) {
+        return None;
+    }
+
+    let mut image = texture.clone();
+    let (width, height) = (texture.width(), texture.height());
+    for y in 0..height {
+        for x in 0..width {
+            // rows go down the image, so the north side is at v = 0
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = (y as f32 + 0.5) / height as f32;
+            let distances = [
+                (EdgeMask::NORTH, v),
+                (EdgeMask::EAST, 1.0 - u),
+                (EdgeMask::SOUTH, 1.0 - v),
+                (EdgeMask::WEST, u),
+                (EdgeMask::NORTH_EAST, Vec2::new(1.0 - u, v).length()),
+                (EdgeMask::SOUTH_EAST, Vec2::new(1.0 - u, 1.0 - v).length()),
+                (EdgeMask::SOUTH_WEST, Vec2::new(u, 1.0 - v).length()),
+                (EdgeMask::NORTH_WEST, Vec2::new(u, v).length()),
+            ];
+            let coverage = distances
+                .into_iter()
+                .filter(|(side, _)| mask.contains(*side))
+                .map(|(_, distance)| fade(distance, reach))
+                .fold(0.0, f32::max);
+
+            let alpha = &mut image.data[((y * width + x) * 4 + 3) as usize];
+            *alpha = (*alpha as f32 * coverage).round() as u8;
+        }
+    }
+    Some(image)
+}
+
+/// Fully opaque at the edge, gone at `reach`, easing in and out in between.
+fn fade(distance: f32, reach: f32) -> f32 {
+    if reach <= 0.0 {
+        return 0.0;
+    }
+    let t = (distance / reach).min(1.0);
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
+
+/// Spawns the tilemaps blending neighboring biomes over the map's edges, a layer of them over every
+/// `MapChunk`. Each biome blends its main tile texture, the textures are blended once for every mask.
+pub fn spawn_transition_overlays(
+    commands: &mut Commands,
+    game_map: &GameMap,
+    game_world: &GameWorld,
+    images: &mut Assets<Image>,
+) {
+    let map_size = UVec2::new(game_map.width(), game_map.height());
+    spawn_chunk_overlays(commands, game_map, game_world, images, MapChunk::covering(map_size).collect());
+}
+
+/// Spawns the overlays of the chunks around `cells` again, after the cells changed biome.
+pub fn refresh_transition_overlays(
+    commands: &mut Commands,
+    game_map: &GameMap,
+    game_world: &GameWorld,
+    images: &mut Assets<Image>,
+    overlay_query: &Query<(Entity, &TileStorage, &TransitionOverlay)>,
+    cells: &[UVec2],
+) {
+    // a cell's biome blends over its neighbors, which can be in the next chunk
+    let max = UVec2::new(game_map.width(), game_map.height()) - 1;
+    let mut origins: Vec<UVec2> = cells
+        .iter()
+        .flat_map(|cell| {
+            let (low, high) = (cell.saturating_sub(UVec2::ONE), (*cell + 1).min(max));
+            [low, UVec2::new(high.x, low.y), UVec2::new(low.x, high.y), high]
+        })
+        .map(|cell| cell / MapChunk::SIZE * MapChunk::SIZE)
+        .collect();
+    origins.sort_by_key(|origin| (origin.y, origin.x));
+    origins.dedup();
+
+    for (entity, tile_storage, overlay) in overlay_query.iter() {
+        if origins.contains(&overlay.origin) {
+            despawn_overlay(commands, entity, tile_storage);
+        }
+    }
+    let chunks = origins.into_iter().map(|origin| MapChunk { origin }).collect();
+    spawn_chunk_overlays(commands, game_map, game_world, images, chunks);
+}
+
+fn spawn_chunk_overlays(
+    commands: &mut Commands,
+    game_map: &GameMap,
+    game_world: &GameWorld,
+    images: &mut Assets<Image>,
+    chunks: Vec<MapChunk>,
+) {
+    let map_size = UVec2::new(game_map.width(), game_map.height());
+    let tile_picker = TilePicker::new(game_world, game_map.biomes(), game_map.seed());
+    let biome_textures = game_world.texture_handles_for_all_biomes();
+
+    // every texture has to be blended before the first overlay is spawned, they all share them
+    let mut textures = vec![];
+    let mut texture_indices: HashMap<(BiomeId, EdgeMask), Option<u32>> = HashMap::new();
+    let chunk_overlays: Vec<_> = chunks
+        .into_iter()
+        .map(|chunk| {
+            let chunk_size = chunk.size(map_size);
+            let tile_overlays: Vec<(TilePos, Vec<u32>)> = (0..chunk_size.y)
+                .flat_map(|y| (0..chunk_size.x).map(move |x| TilePos { x, y }))
+                .map(|tile_pos| {
+                    let cell = chunk.cell(&tile_pos);
+                    let cell_overlays = transitions_at(game_map, cell.x, cell.y)
+                        .into_iter()
+                        .filter_map(|transition| {
+                            *texture_indices.entry((transition.biome_id, transition.mask)).or_insert_with(|| {
+                                let biome = &game_map.biomes()[transition.biome_id.index()];
+                                let handle = &biome_textures[tile_picker.main_texture(transition.biome_id)? as usize];
+                                let reach = biome.transitions.as_ref()?.reach;
+                                let blended = blend_edges(images.get(handle)?, transition.mask, reach)?;
+                                textures.push(images.add(blended));
+                                Some(textures.len() as u32 - 1)
+                            })
+                        })
+                        .collect();
+                    (tile_pos, cell_overlays)
+                })
+                .collect();
+            (chunk, chunk_size, tile_overlays)
+        })
+        .collect();
+    if textures.is_empty() {
+        return;
+    }
+    let texture = TilemapTexture::Vector(textures);
+
+    for (chunk, chunk_size, tile_overlays) in chunk_overlays {
+        let layers = tile_overlays.iter().map(|(_, cell_overlays)| cell_overlays.len()).max().unwrap_or(0);
+
+        for layer in 0..layers {
+            let mut tile_storage = TileStorage::empty(chunk_size.into());
+            let tilemap_entity = commands.spawn_empty().id();
+            for (tile_pos, cell_overlays) in &tile_overlays {
+                let Some(texture_index) = cell_overlays.get(layer) else {
+                    continue;
+                };
+                let tile = commands.spawn(TileBundle {
+                    position: *tile_pos,
+                    tilemap_id: TilemapId(tilemap_entity),
+                    texture_index: TileTextureIndex(*texture_index),
+                    ..default()
+                });
+                tile_storage.set(tile_pos, tile.id());
+            }
+
+            let z = MAP_Z + OVERLAY_LAYER_Z * (layer + 1) as f32;
+            commands.entity(tilemap_entity).insert((
+                chunk_tilemap(game_world, &chunk, chunk_size, tile_storage, texture.clone(), z),
+                TransitionOverlay { origin: chunk.origin, layer: layer as u32 },
+                Name::new("Transition Overlay"),
+            ));
+        }
+    }
+}
+
+/// Despawns the transition overlays along with their tiles, so they can be spawned again.
+pub fn despawn_transition_overlays(
+    commands: &mut Commands,
+    overlay_query: &Query<(Entity, &TileStorage), With<TransitionOverlay>>,
+) {
+    for (entity, tile_storage) in overlay_query.iter() {
+        despawn_overlay(commands, entity, tile_storage);
+    }
+}
+
+fn despawn_overlay(commands: &mut Commands, entity: Entity, tile_storage: &TileStorage) {
+    for tile in tile_storage.iter().flatten() {
+        commands.entity(*tile).despawn();
+    }
+    commands.entity(entity).despawn();
+}
@@ -1,35 +1,114 @@
 use crate::{
     components::{
-        entity_selected::*, entity_selected_actions::EntitySelectedActions, nickname::*,
-        plant::Plant,
+        entity_selected::*, entity_selected_actions::EntitySelectedActions, nickname::*, plant::Plant,
+        work_priorities::WorkPriorities,
     },
-    resources::jobs::*, GameState,
+    resources::{
+        day_cycle::DayCycle,
+        game_errors::GameErrors,
+        input_actions::{ActionState, InputAction},
+        jobs::*,
+        mods::ActiveMods,
+    },
+    systems::{asset_loading::*, map_generation::MapGenerationTask},
+    GameState,
 };
 use bevy::{ecs::system::SystemState, prelude::*, window::PrimaryWindow};
 use bevy_egui::{egui::{self, *}, EguiContext, EguiContexts};
 
-pub fn run_loading_ui(mut ctx: EguiContexts, state: Res<State<GameState>>) {
+pub fn run_loading_ui(
+    mut ctx: EguiContexts,
+    state: Res<State<GameState>>,
+    game_errors: Res<GameErrors>,
+    loading_assets: Option<Res<LoadingAssets>>,
+    active_mods: Res<ActiveMods>,
+    map_generation_task: Option<Res<MapGenerationTask>>,
+    mut retry_events: EventWriter<RetryFailedAssets>,
+) {
     if let Some(ctx) = ctx.try_ctx_mut() {
-        egui::CentralPanel::default().show(&ctx, |ui| {
-            ui.centered_and_justified(|ui| {
-                ui.label(RichText::new(match state.get() {
-                    GameState::MapGeneration => "Generating the map...",
-                    _ => "Loading...",
-                }).font(FontId::proportional(40.0)));
+        if show_game_errors(ctx, &game_errors) {
+            return;
+        }
+
+        let (text, progress) = match state.get() {
+            GameState::MapGeneration => (
+                "Generating the map...".to_string(),
+                map_generation_task.map(|task| task.progress()),
+            ),
+            _ => match loading_assets.as_ref().map(|loading_assets| loading_assets.progress()) {
+                Some((loaded, total)) => (
+                    format!("Loading... {loaded}/{total}"),
+                    Some(loaded as f32 / total.max(1) as f32),
+                ),
+                None => ("Loading...".to_string(), None),
+            },
+        };
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.label(RichText::new(text).font(FontId::proportional(40.0)));
+                if let Some(progress) = progress {
+                    ui.add(ProgressBar::new(progress).desired_width(ui.available_width() / 2.0));
+                }
+
+                if !active_mods.mods.is_empty() || !active_mods.disabled.is_empty() {
+                    ui.add_space(20.0);
+                    ui.heading("Mods");
+                    for active_mod in active_mods.mods.iter() {
+                        ui.label(format!("{} {}", active_mod.manifest.name(), active_mod.manifest.version));
+                    }
+                    for (folder, reason) in active_mods.disabled.iter() {
+                        ui.label(RichText::new(format!("{}: {reason}", folder.display())).color(Color32::YELLOW));
+                    }
+                }
+
+                let Some(loading_assets) = loading_assets else {
+                    return;
+                };
+                let failed: Vec<_> = loading_assets.failed().collect();
+                if failed.is_empty() {
+                    return;
+                }
+
+                ui.add_space(20.0);
+                ui.heading(RichText::new("Some assets failed to load").color(Color32::LIGHT_RED));
+                for (path, error) in failed {
+                    ui.label(RichText::new(format!("{}: {error}", path.display())).color(Color32::LIGHT_RED));
+                }
+                if ui.button("Retry").clicked() {
+                    retry_events.send(RetryFailedAssets);
+                }
             });
         });
     }
 }
 
-pub fn run_main_ui<'a>(
+/// Covers the screen with the errors that stopped the game, returns whether there were any.
+pub fn show_game_errors(ctx: &egui::Context, game_errors: &GameErrors) -> bool {
+    if game_errors.0.is_empty() {
+        return false;
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.heading(RichText::new("The game could not continue").font(FontId::proportional(40.0)));
+            for error in game_errors.0.iter() {
+                ui.label(RichText::new(error).color(Color32::LIGHT_RED));
+            }
+        });
+    });
+    true
+}
+
+type SelectedPlant = (With<EntitySelected>, With<Plant>);
+
+pub fn run_main_ui(
     world: &mut World,
     selected_query: &mut QueryState<&Nickname, With<EntitySelected>>,
     egui_context_query: &mut QueryState<&mut EguiContext, With<PrimaryWindow>>,
-    plant_query: &mut QueryState<
-        (Entity, &EntitySelectedActions<'static>),
-        (With<EntitySelected>, With<Plant>),
-    >,
-    state: &mut SystemState<Res<Jobs>>,
+    plant_query: &mut QueryState<(Entity, &EntitySelectedActions<'static>), SelectedPlant>,
+    state: &mut SystemState<(Res<Jobs>, Res<DayCycle>, Query<&Nickname>)>,
 ) {
     // query for the egui context
     let Ok(ctx) = egui_context_query.get_single(world) else {
@@ -38,6 +117,12 @@ pub fn run_main_ui<'a>(
     let mut ctx = ctx.clone();
     let ctx = ctx.get_mut();
 
+    let (hours, minutes) = state.get(world).1.hours_minutes();
+
+    egui::TopBottomPanel::top("time_panel").show(ctx, |ui| {
+        ui.label(format!("Time: {hours:02}:{minutes:02}"));
+    });
+
     egui::TopBottomPanel::bottom("selected_panel").show(ctx, |ui| {
         ui.vertical(|ui| {
             ui.label(format!(
@@ -52,7 +137,7 @@ pub fn run_main_ui<'a>(
                 plant_query.get_single(world)
             {
                 let action_definitions: Vec<_> = action_definitions
-                    .into_iter()
+                    .iter()
                     .map(|w| (w.name.clone(), w.action, w.is_visible))
                     .collect();
 
@@ -69,25 +154,104 @@ pub fn run_main_ui<'a>(
         });
     });
 
-    // query the jobs resource
-    let jobs = state
-        .get(world)
-        .0
+    // query the jobs resource, with who's on them
+    let (jobs, _, nickname_query) = state.get(world);
+    let nickname = |pawn: Entity| nickname_query.get(pawn).map_or_else(|_| pawn.to_string(), |Nickname(nickname)| nickname.clone());
+    let jobs = jobs
         .iter()
-        .map(|w| (w.name.clone(), w.job_type.clone()))
+        .map(|w| {
+            let state = match w.state() {
+                JobState::Open => String::new(),
+                JobState::Reserved(pawn) => format!(" - {} is on the way", nickname(*pawn)),
+                JobState::InProgress(pawn) => format!(" - {} is on it", nickname(*pawn)),
+                JobState::Failed { reason, .. } => format!(" - failed: {reason}"),
+            };
+            (w.name.clone(), w.job_type.clone(), state)
+        })
         .collect::<Vec<_>>();
 
     egui::SidePanel::right("Jobs").show(ctx, |ui| {
         ui.vertical(|ui| {
             ui.heading("Jobs:");
 
-            for (name, job_type) in jobs {
+            for (name, job_type, state) in jobs {
                 match job_type {
                     JobType::PlantHarvest(entity) => {
-                        ui.label(format!("{name} [{entity}]"));
+                        ui.label(format!("{name} [{entity}]{state}"));
+                    }
+                    JobType::Mine(cell) => {
+                        ui.label(format!("{name} [{}, {}]{state}", cell.x, cell.y));
                     }
                 }
             }
         });
     });
 }
+
+/// The window setting which work every pawn does, and how eagerly.
+#[derive(Resource, Default)]
+pub struct WorkPrioritiesWindow {
+    pub open: bool,
+}
+
+pub fn toggle_work_priorities_window(actions: Res<ActionState>, mut window: ResMut<WorkPrioritiesWindow>) {
+    if actions.just_pressed(InputAction::ToggleWorkPriorities) {
+        window.open = !window.open;
+    }
+}
+
+/// A grid of the pawns by the kinds of work, clicking a priority steps it to the next one, right clicking back.
+pub fn run_work_priorities_ui(
+    mut ctx: EguiContexts,
+    mut window: ResMut<WorkPrioritiesWindow>,
+    mut pawn_query: Query<(Entity, &Nickname, &mut WorkPriorities)>,
+) {
+    let Some(ctx) = ctx.try_ctx_mut() else {
+        return;
+    };
+    let mut pawns: Vec<_> = pawn_query.iter_mut().collect();
+    pawns.sort_by_key(|(entity, _, _)| *entity);
+
+    egui::Window::new("Work priorities")
+        .open(&mut window.open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            Grid::new("work_priorities_grid").num_columns(WorkType::ALL.len() + 1).spacing([12.0, 4.0]).show(ui, |ui| {
+                ui.label("");
+                for work_type in WorkType::ALL {
+                    ui.label(work_type.label());
+                }
+                ui.end_row();
+
+                for (_, Nickname(nickname), priorities) in pawns.iter_mut() {
+                    ui.label(nickname.as_str());
+                    for work_type in WorkType::ALL {
+                        let priority = priorities.get(work_type);
+                        let text = match priority {
+                            Some(priority) => RichText::new(priority.to_string()).color(priority_color(priority)),
+                            None => RichText::new("-").color(Color32::DARK_GRAY),
+                        };
+                        let button = ui.add(egui::Button::new(text).min_size(egui::vec2(28.0, 0.0)));
+                        if button.clicked() {
+                            priorities.set(work_type, WorkPriorities::next(priority));
+                        } else if button.secondary_clicked() {
+                            // stepping forward around all of them lands on the one before
+                            let previous = (0..WorkPriorities::LOWEST).fold(priority, |priority, _| WorkPriorities::next(priority));
+                            priorities.set(work_type, previous);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn priority_color(priority: u8) -> Color32 {
+    match priority {
+        1 => Color32::LIGHT_GREEN,
+        2 => Color32::from_rgb(210, 230, 150),
+        3 => Color32::LIGHT_GRAY,
+        _ => Color32::GRAY,
+    }
+}
@@ -0,0 +1,41 @@
+use crate::components::walk_path::WalkPath;
+use crate::resources::game_map::GameMap;
+use crate::GameWorld;
+use bevy::prelude::*;
+
+/// How many cells a second pawns walk over ground with a movement modifier of 1.
+pub const WALK_SPEED: f32 = 2.0;
+
+/// Moves pawns along their paths, slower over ground that's hard going. A pawn stops where it is when the
+/// cell ahead can't be walked on anymore.
+pub fn walk_paths(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_map: Res<GameMap>,
+    game_world: Res<GameWorld>,
+    mut pawn_query: Query<(Entity, &mut Transform, &mut WalkPath)>,
+) {
+    for (pawn, mut transform, mut path) in pawn_query.iter_mut() {
+        let mut time_left = time.delta_seconds();
+        while let Some(next) = path.0.first().copied() {
+            let movement_modifier = game_map.movement_modifier(next.x, next.y) as f32;
+            if movement_modifier <= 0.0 {
+                path.0.clear();
+                break;
+            }
+            let speed = WALK_SPEED * game_world.cell_size() as f32 * movement_modifier;
+            let (position, target) = (transform.translation.truncate(), game_world.cell_center(next));
+            let distance = position.distance(target);
+            if distance > speed * time_left {
+                transform.translation += ((target - position) / distance * speed * time_left).extend(0.0);
+                break;
+            }
+            transform.translation = target.extend(transform.translation.z);
+            time_left -= distance / speed;
+            path.0.remove(0);
+        }
+        if path.0.is_empty() {
+            commands.entity(pawn).remove::<WalkPath>();
+        }
+    }
+}
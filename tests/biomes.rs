@@ -0,0 +1,217 @@
+use std::path::Path;
+
+use meadowland::resources::biome_registry::{BiomeId, BiomeRegistry};
+use meadowland::resources::biomes::Biomes;
+use meadowland::systems::rivers::RiverParameters;
+use meadowland::systems::map_generation::{
+    generate_map, generate_map_serially, generate_map_with_progress, MapGenerationProgress, MapParameters,
+};
+
+fn parse(json: &str) -> Biomes {
+    serde_json::from_str(json).unwrap()
+}
+
+fn parameters(seed: u64, biome_weights: Vec<f64>) -> MapParameters {
+    MapParameters { width: 32, height: 32, seed, biome_weights, rivers: RiverParameters::default() }
+}
+
+#[test]
+fn shipped_biome_table_is_valid() {
+    let biomes = parse(&std::fs::read_to_string("assets/biomes/details.biomes.json").unwrap());
+    assert_eq!(biomes.validate(), Ok(()));
+}
+
+#[test]
+fn invalid_biome_table_reports_every_problem() {
+    let biomes = parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1, "minHeight": 0.2, "minMoisture": 0, "minHeat": 0 },
+            { "name": "Grass", "movementModifier": -1, "minHeight": 1.5, "minMoisture": 0, "minHeat": 0 }
+        ] }"#,
+    );
+
+    let errors = biomes.validate().unwrap_err();
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    assert!(errors.iter().any(|error| error.contains("more than once")));
+    assert!(errors.iter().any(|error| error.contains("movement modifier")));
+    assert!(errors.iter().any(|error| error.contains("minHeight 1.5")));
+    assert!(errors.iter().any(|error| error.contains("thresholds at 0")));
+}
+
+#[test]
+fn the_merged_tables_have_at_most_one_river() {
+    let base = parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1 },
+            { "name": "River", "movementModifier": 0.5, "kind": "river" }
+        ] }"#,
+    );
+    let creeks = parse(r#"{ "biomes": [{ "name": "Creek", "movementModifier": 0.5, "kind": "river" }] }"#);
+
+    let errors = BiomeRegistry::from_tables([
+        (Path::new("biomes/base.biomes.json"), &base),
+        (Path::new("mods/creeks/biomes/creeks.biomes.json"), &creeks),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(
+        errors,
+        ["Only one biome can be a river, but River from biomes/base.biomes.json, Creek from mods/creeks/biomes/creeks.biomes.json are"]
+    );
+
+    // a mod can take the river over instead
+    let dry_river = parse(r#"{ "biomes": [{ "name": "River", "movementModifier": 1, "kind": "floor" }] }"#);
+    let registry = BiomeRegistry::from_tables([
+        (Path::new("biomes/base.biomes.json"), &base),
+        (Path::new("mods/creeks/biomes/dry.biomes.json"), &dry_river),
+        (Path::new("mods/creeks/biomes/creeks.biomes.json"), &creeks),
+    ]);
+    assert!(registry.is_ok());
+}
+
+#[test]
+fn tile_weights_match_texture_names_and_stay_in_range() {
+    let biomes = parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1, "tileWeights": [
+                { "texture": "grass_flowers_*", "weight": 0.1, "patchSize": 8 },
+                { "texture": "grass_0", "weight": -1 },
+                { "texture": "grass_1", "weight": 1, "patchSize": 0.5 }
+            ] }
+        ] }"#,
+    );
+
+    let tile_weights = &biomes.biomes[0].tile_weights;
+    assert!(tile_weights[0].matches("grass_flowers_red_1"));
+    assert!(!tile_weights[0].matches("grass_0"));
+    assert!(tile_weights[1].matches("grass_0"));
+    assert!(!tile_weights[1].matches("grass_0_new"));
+    assert_eq!(
+        biomes.validate(),
+        Err(vec![
+            "Biome Grass has a negative weight for grass_0".to_string(),
+            "Biome Grass has patches of grass_1 smaller than a cell".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn unmatched_cells_fall_back_to_the_closest_biome() {
+    let biomes = parse(
+        r#"{ "biomes": [
+            { "name": "Hills", "movementModifier": 1, "minHeight": 0.9, "minMoisture": 0.9, "minHeat": 0.9 },
+            { "name": "Peaks", "movementModifier": 1, "minHeight": 0.95, "minMoisture": 0.95, "minHeat": 0.95 }
+        ] }"#,
+    )
+    .biomes;
+
+    let cells = generate_map(&biomes, &parameters(7, vec![1.0; biomes.len()]));
+    assert!(cells.iter().any(|cell| !cell.matched));
+    assert!(cells.iter().all(|cell| cell.biome_id.index() < biomes.len()));
+
+    let again = generate_map(&biomes, &parameters(7, vec![1.0; biomes.len()]));
+    assert!(cells.iter().zip(&again).all(|(a, b)| a.biome_id == b.biome_id));
+}
+
+#[test]
+fn biomes_weighing_nothing_are_only_used_where_nothing_else_fits() {
+    let biomes = parse(GRASS_AND_WATER).biomes;
+
+    let cells = generate_map(&biomes, &parameters(7, vec![1.0, 1.0]));
+    assert!(cells.iter().any(|cell| cell.biome_id == BiomeId(1)));
+
+    // water fits everywhere, so there's no grass left
+    let cells = generate_map(&biomes, &parameters(7, vec![1.0, 0.0]));
+    assert!(cells.iter().all(|cell| cell.biome_id == BiomeId(0) && cell.matched));
+}
+
+#[test]
+fn chunked_generation_matches_the_serial_generation() {
+    let biomes = parse(&std::fs::read_to_string("assets/biomes/details.biomes.json").unwrap()).biomes;
+
+    // sizes that don't split evenly into chunks, and one smaller than a chunk
+    for (width, height) in [(37, 53), (64, 5), (100, 100)] {
+        let parameters = MapParameters {
+            width,
+            height,
+            seed: 3,
+            biome_weights: vec![1.0; biomes.len()],
+            rivers: RiverParameters { count: 5, ..Default::default() },
+        };
+        let cells = generate_map(&biomes, &parameters);
+        assert_eq!(cells.len(), (width * height) as usize);
+        assert_eq!(cells, generate_map_serially(&biomes, &parameters));
+    }
+}
+
+#[test]
+fn cancelled_generation_stops_without_a_map() {
+    let biomes = parse(GRASS_AND_WATER).biomes;
+    let progress = MapGenerationProgress::default();
+    progress.cancel();
+
+    assert!(generate_map_with_progress(&biomes, &parameters(7, vec![1.0, 1.0]), &progress).is_none());
+    assert_eq!(progress.rows_done(), 0);
+}
+
+const GRASS_AND_WATER: &str = r#"{ "biomes": [
+    { "name": "Water", "movementModifier": 0, "minHeight": 0, "minMoisture": 0, "minHeat": 0 },
+    { "name": "Grass", "movementModifier": 1, "minHeight": 0.3, "minMoisture": 0, "minHeat": 0 }
+] }"#;
+
+#[test]
+fn mods_override_biomes_and_keep_their_ids() {
+    let base = parse(GRASS_AND_WATER);
+    let wetlands = parse(
+        r#"{ "biomes": [
+            { "name": "Marsh", "movementModifier": 0.5, "minHeight": 0.2, "minMoisture": 0.6, "minHeat": 0 },
+            { "name": "Water", "movementModifier": 0.25, "minHeight": 0, "minMoisture": 0, "minHeat": 0 }
+        ] }"#,
+    );
+
+    let registry = BiomeRegistry::from_tables([
+        (Path::new("biomes/base.biomes.json"), &base),
+        (Path::new("mods/wetlands/biomes/wetlands.biomes.json"), &wetlands),
+    ])
+    .unwrap();
+
+    let names: Vec<_> = registry.iter().map(|(id, biome)| (id, biome.name.as_str())).collect();
+    assert_eq!(names, [(BiomeId(0), "Water"), (BiomeId(1), "Grass"), (BiomeId(2), "Marsh")]);
+    assert_eq!(registry.get(BiomeId(0)).movement_modifier, 0.25);
+}
+
+#[test]
+fn the_same_biome_in_two_files_of_one_layer_is_a_conflict() {
+    let base = parse(GRASS_AND_WATER);
+    let more_grass = parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 0.8, "minHeight": 0.3, "minMoisture": 0, "minHeat": 0 }
+        ] }"#,
+    );
+
+    let errors = BiomeRegistry::from_tables([
+        (Path::new("biomes/a.biomes.json"), &base),
+        (Path::new("biomes/b.biomes.json"), &more_grass),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(errors, ["Biome Grass is defined in both biomes/a.biomes.json and biomes/b.biomes.json"]);
+}
+
+#[test]
+fn reloading_keeps_the_ids_of_removed_biomes() {
+    let base = parse(GRASS_AND_WATER);
+    let mut registry = BiomeRegistry::from_tables([(Path::new("biomes/base.biomes.json"), &base)]).unwrap();
+
+    let edited = parse(
+        r#"{ "biomes": [
+            { "name": "Sand", "movementModifier": 0.9, "minHeight": 0.2, "minMoisture": 0, "minHeat": 0 },
+            { "name": "Water", "movementModifier": 0, "minHeight": 0, "minMoisture": 0, "minHeat": 0 }
+        ] }"#,
+    );
+    registry.update(BiomeRegistry::from_tables([(Path::new("biomes/base.biomes.json"), &edited)]).unwrap());
+
+    assert_eq!(registry.id_of("Water"), Some(BiomeId(0)));
+    assert_eq!(registry.id_of("Grass"), Some(BiomeId(1)));
+    assert_eq!(registry.id_of("Sand"), Some(BiomeId(2)));
+}
@@ -0,0 +1,537 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_ecs_tilemap::map::TilemapId;
+use bevy_ecs_tilemap::tiles::{TilePos, TileTextureIndex};
+use meadowland::components::{light_source::LightSource, map_chunk::MapChunk, pawn::Pawn, plant::Plant};
+use meadowland::components::transition_overlay::TransitionOverlay;
+use meadowland::components::{mine_designation::MineDesignation, resource_stack::ResourceStack};
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::components::tile_occupant::TileOccupant;
+use meadowland::components::work_priorities::WorkPriorities;
+use meadowland::components::plant_harvest::PlantHarvest;
+use meadowland::resources::jobs::{JobEvent, JobState, JobType, Jobs, WorkType};
+use meadowland::resources::path_finder::PathFinder;
+use meadowland::resources::spatial_index::SpatialIndex;
+use meadowland::resources::{biomes::Biomes, game_map::{GameMap, MapCellsChanged}, mods::ActiveMods, new_game::NewGameSettings};
+use meadowland::plugins::{game_world_plugin::*, headless_plugins::HeadlessPlugins};
+use meadowland::systems::asset_loading::LoadingAssets;
+use meadowland::systems::map_generation::MapPreview;
+use meadowland::systems::mining::ToggleMineDesignation;
+use meadowland::systems::transitions::transitions_at;
+
+fn headless_app(width: u32, height: u32) -> App {
+    headless_app_with_assets(width, height, "assets")
+}
+
+fn headless_app_with_assets(width: u32, height: u32, asset_folder: &str) -> App {
+    let mut app = headless_menu_app(asset_folder);
+    app.insert_resource(NewGameSettings { width, height, seed: 7, ..default() })
+        .add_systems(OnEnter(GameState::MainMenu), start_new_game);
+    app
+}
+
+/// An app that stays in the menus until it's sent on.
+fn headless_menu_app(asset_folder: &str) -> App {
+    let asset_plugin = AssetPlugin {
+        file_path: asset_folder.to_string(),
+        ..default()
+    };
+
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugins.set(asset_plugin))
+        .add_plugins(GameWorldPlugin);
+    // `App::run` would do this, some asset loaders are only registered when plugins finish
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn set_state(app: &mut App, state: GameState) {
+    app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+}
+
+/// Skips the menus, straight to generating a map with the app's `NewGameSettings`.
+fn start_new_game(mut app_next_state: ResMut<NextState<GameState>>) {
+    app_next_state.set(GameState::MapGeneration);
+}
+
+/// Steps the app until it reaches `state`, asset loading and map generation run on other threads.
+fn run_until_state(app: &mut App, state: GameState) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while *app.world().resource::<State<GameState>>().get() != state {
+        assert!(Instant::now() < deadline, "timed out waiting for {state:?}");
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn headless_game_reaches_main_state() {
+    let mut app = headless_app(40, 30);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let world = app.world_mut();
+    let tilemaps: Vec<_> = world.query_filtered::<&TilemapId, With<TilePos>>().iter(world).map(|id| id.0).collect();
+    let map_tiles = tilemaps.iter().filter(|tilemap| world.get::<MapChunk>(**tilemap).is_some()).count();
+    assert_eq!(map_tiles, 40 * 30);
+    assert_eq!(world.query_filtered::<(), With<Pawn>>().iter(world).count(), 3);
+    assert!(world.query_filtered::<(), With<Plant>>().iter(world).count() > 0);
+}
+
+#[test]
+fn large_maps_are_split_into_chunks() {
+    let mut app = headless_app(100, 70);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let world = app.world_mut();
+    let mut origins: Vec<_> = world.query::<&MapChunk>().iter(world).map(|chunk| chunk.origin).collect();
+    origins.sort_by_key(|origin| (origin.y, origin.x));
+    assert_eq!(origins, [UVec2::new(0, 0), UVec2::new(64, 0), UVec2::new(0, 64), UVec2::new(64, 64)]);
+
+    // every tile shows its own cell's biome, under the transitions
+    let tiles: Vec<_> = world
+        .query::<(&TilePos, &TilemapId, &TileTextureIndex)>()
+        .iter(world)
+        .filter_map(|(tile_pos, tilemap_id, texture_index)| {
+            let chunk = world.get::<MapChunk>(tilemap_id.0)?;
+            Some((chunk.cell(tile_pos), texture_index.0))
+        })
+        .collect();
+    assert_eq!(tiles.len(), 100 * 70);
+    let game_map = world.resource::<GameMap>();
+    let game_world = world.resource::<GameWorld>();
+    for (cell, texture_index) in tiles {
+        let textures = game_world.texture_handles_for_biome(game_map.biome_at(cell.x, cell.y));
+        assert!(textures.iter().any(|(_, index)| *index == texture_index), "wrong texture at {cell}");
+    }
+}
+
+#[test]
+fn biome_edges_get_transition_overlays() {
+    let mut app = headless_app(100, 70);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let world = app.world_mut();
+    let overlay_tiles: Vec<_> = world
+        .query::<(&TilePos, &TilemapId)>()
+        .iter(world)
+        .filter_map(|(tile_pos, tilemap_id)| {
+            let overlay = world.get::<TransitionOverlay>(tilemap_id.0)?;
+            Some((overlay.origin + UVec2::new(tile_pos.x, tile_pos.y), overlay.layer))
+        })
+        .collect();
+    assert!(!overlay_tiles.is_empty());
+
+    // an overlay on every cell with a neighbor blending over it, and on no other
+    let game_map = world.resource::<GameMap>();
+    for y in 0..70 {
+        for x in 0..100 {
+            let layers = transitions_at(game_map, x, y).len();
+            let overlays = overlay_tiles.iter().filter(|(cell, _)| *cell == UVec2::new(x, y)).count();
+            assert_eq!(overlays, layers, "at {x}, {y}");
+        }
+    }
+}
+
+/// The texture of every map tile by its cell, leaving out the transition overlays.
+fn map_tile_textures(app: &mut App) -> Vec<(UVec2, u32)> {
+    let world = app.world_mut();
+    let mut tiles: Vec<_> = world
+        .query::<(&TilePos, &TilemapId, &TileTextureIndex)>()
+        .iter(world)
+        .filter_map(|(tile_pos, tilemap_id, texture_index)| {
+            let chunk = world.get::<MapChunk>(tilemap_id.0)?;
+            Some((chunk.cell(tile_pos), texture_index.0))
+        })
+        .collect();
+    tiles.sort_by_key(|(cell, _)| (cell.y, cell.x));
+    tiles
+}
+
+#[test]
+fn tile_textures_are_weighted_clustered_and_the_same_for_a_seed() {
+    let mut app = headless_app(100, 70);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+    let tiles = map_tile_textures(&mut app);
+
+    let mut again = headless_app(100, 70);
+    run_until_state(&mut again, GameState::Main);
+    again.update();
+    assert_eq!(map_tile_textures(&mut again), tiles);
+
+    let game_world = app.world().resource::<GameWorld>();
+    let flower_textures: Vec<_> = game_world
+        .texture_handles_for_all_biomes()
+        .iter()
+        .enumerate()
+        .filter(|(_, handle)| handle.path().is_some_and(|path| path.to_string().contains("grass_flowers")))
+        .map(|(index, _)| index as u32)
+        .collect();
+    let is_flower = |texture: u32| flower_textures.contains(&texture);
+    let game_map = app.world().resource::<GameMap>();
+    let grass_tiles: Vec<_> = tiles
+        .iter()
+        .filter(|(cell, _)| game_map.biome_at(cell.x, cell.y).tile_name() == "Grass")
+        .collect();
+    let flowers: Vec<_> = grass_tiles.iter().filter(|(_, texture)| is_flower(*texture)).collect();
+
+    // most of the grass is plain, the flowers are a few percent of it
+    assert!(!flowers.is_empty());
+    let flower_share = flowers.len() as f64 / grass_tiles.len() as f64;
+    assert!(flower_share < 0.1, "{flower_share}");
+
+    // and they grow in patches, a flower is much more likely to be next to another one than by chance
+    let texture_at = |cell: UVec2| tiles[(cell.y * 100 + cell.x) as usize].1;
+    let flower_next_to_flower = flowers
+        .iter()
+        .filter(|(cell, _)| {
+            [(1, 0), (0, 1), (-1, 0), (0, -1)].iter().any(|(dx, dy)| {
+                let neighbor = cell.as_ivec2() + IVec2::new(*dx, *dy);
+                neighbor.cmpge(IVec2::ZERO).all()
+                    && neighbor.cmplt(IVec2::new(100, 70)).all()
+                    && is_flower(texture_at(neighbor.as_uvec2()))
+            })
+        })
+        .count();
+    let clustered_share = flower_next_to_flower as f64 / flowers.len() as f64;
+    let by_chance = 1.0 - (1.0 - flower_share).powi(4);
+    assert!(clustered_share > 2.0 * by_chance, "{clustered_share} next to each other, {by_chance} by chance");
+}
+
+#[test]
+fn pawns_mine_out_designated_rock() {
+    let mut app = headless_app(100, 70);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+    // virtual time never steps more than a quarter of a second at once
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)));
+
+    let biome_id = |app: &App, name: &str| {
+        let index = app.world().resource::<GameMap>().biomes().iter().position(|biome| biome.name == name);
+        BiomeId(index.unwrap() as u16)
+    };
+    let (grass, mountain, floor) = (biome_id(&app, "Grass"), biome_id(&app, "Mountain"), biome_id(&app, "MountainFloor"));
+    // the villagers leave mining be for now, the rock is a few cells of grass away from one of them
+    let world = app.world_mut();
+    for mut priorities in world.query::<&mut WorkPriorities>().iter_mut(world) {
+        priorities.set(WorkType::Mine, None);
+    }
+    let villager = world.query_filtered::<Entity, With<WorkPriorities>>().iter(world).min().unwrap();
+    let villager_cell = world.resource::<SpatialIndex>().cell_of(villager).unwrap();
+    let (rock, meadow) = (villager_cell - UVec2::new(6, 0), UVec2::new(10, 10));
+    let mut game_map = world.resource_mut::<GameMap>();
+    let mut changed = vec![rock, meadow];
+    game_map.set_biome(rock.x, rock.y, mountain);
+    game_map.set_biome(meadow.x, meadow.y, grass);
+    for x in rock.x - 1..villager_cell.x {
+        if x != rock.x {
+            game_map.set_biome(x, rock.y, grass);
+            changed.push(UVec2::new(x, rock.y));
+        }
+    }
+    world.send_event(MapCellsChanged(changed));
+
+    // only rock takes the mark
+    app.world_mut().send_event(ToggleMineDesignation(meadow));
+    app.world_mut().send_event(ToggleMineDesignation(rock));
+    app.update();
+    let mine_jobs = |app: &App| -> Vec<UVec2> {
+        let jobs = app.world().resource::<Jobs>();
+        jobs.iter().filter_map(|job| match job.job_type {
+            JobType::Mine(cell) => Some(cell),
+            _ => None,
+        }).collect()
+    };
+    assert_eq!(mine_jobs(&app), [rock]);
+    let events = app.world().resource::<Events<JobEvent>>();
+    assert!(events.iter_current_update_events().any(|event| matches!(event, JobEvent::Posted(_))));
+
+    // a pawn that didn't take the job doesn't dig, even standing right next to the rock
+    let position = app.world().resource::<GameWorld>().cell_center(rock - UVec2::X);
+    app.world_mut().spawn((Pawn, Transform::from_translation(position.extend(0.0))));
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<GameMap>().biome_id_at(rock.x, rock.y), mountain);
+
+    // a villager that takes it walks the 5 cells over in 2.5 seconds, then takes the mountain's 4 seconds of work.
+    // They'd rather harvest the tree marked for it, but no pawn harvests yet
+    let world = app.world_mut();
+    for mut priorities in world.query::<&mut WorkPriorities>().iter_mut(world) {
+        priorities.set(WorkType::Mine, Some(2));
+        priorities.set(WorkType::Harvest, Some(1));
+    }
+    let tree = world.query_filtered::<Entity, With<Plant>>().iter(world).next().unwrap();
+    world.entity_mut(tree).insert(PlantHarvest);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<GameMap>().biome_id_at(rock.x, rock.y), mountain);
+    let harvest = app.world().resource::<Jobs>().find(&JobType::PlantHarvest(tree)).map(|job| job.state().clone());
+    assert_eq!(harvest, Some(JobState::Open));
+    let holder = app.world().resource::<Jobs>().iter().find_map(|job| job.holder()).unwrap();
+    for _ in 0..20 {
+        if app.world().resource::<GameMap>().biome_id_at(rock.x, rock.y) == floor {
+            break;
+        }
+        app.update();
+    }
+    let holder_cell = app.world().resource::<SpatialIndex>().cell_of(holder).unwrap();
+    assert!(holder_cell.as_ivec2().distance_squared(rock.as_ivec2()) <= 2, "{holder_cell} isn't next to {rock}");
+
+    let game_map = app.world().resource::<GameMap>();
+    assert_eq!(game_map.biome_id_at(rock.x, rock.y), floor);
+    assert_eq!(game_map.movement_modifier(rock.x, rock.y), 1.0);
+    assert_eq!(mine_jobs(&app), []);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&MineDesignation>().iter(world).count(), 0);
+    let stacks: Vec<_> = world
+        .query::<(&ResourceStack, &Transform)>()
+        .iter(world)
+        .map(|(stack, transform)| (stack.resource.clone(), stack.amount, transform.translation.truncate()))
+        .collect();
+    let rock_position = app.world().resource::<GameWorld>().cell_center(rock);
+    assert_eq!(stacks, [("Stone".to_string(), 2, rock_position)]);
+
+    // the cell shows the floor now, with the overlays around it blended again
+    let floor_textures: Vec<_> = {
+        let game_world = app.world().resource::<GameWorld>();
+        let game_map = app.world().resource::<GameMap>();
+        game_world.texture_handles_for_biome(&game_map.biomes()[floor.index()]).into_iter().map(|(_, index)| index).collect()
+    };
+    let tiles = map_tile_textures(&mut app);
+    assert!(floor_textures.contains(&tiles[(rock.y * 100 + rock.x) as usize].1));
+    let world = app.world_mut();
+    let overlay_cells: Vec<_> = world
+        .query::<(&TilePos, &TilemapId)>()
+        .iter(world)
+        .filter_map(|(tile_pos, tilemap_id)| {
+            let overlay = world.get::<TransitionOverlay>(tilemap_id.0)?;
+            Some(overlay.origin + UVec2::new(tile_pos.x, tile_pos.y))
+        })
+        .collect();
+    let game_map = world.resource::<GameMap>();
+    for y in rock.y - 1..=rock.y + 1 {
+        for x in rock.x - 1..=rock.x + 1 {
+            let overlays = overlay_cells.iter().filter(|cell| **cell == UVec2::new(x, y)).count();
+            assert_eq!(overlays, transitions_at(game_map, x, y).len(), "at {x}, {y}");
+        }
+    }
+}
+
+#[test]
+fn entities_on_the_map_are_indexed_by_cell() {
+    let mut app = headless_app(60, 40);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let world = app.world_mut();
+    let pawn_positions: Vec<_> = world
+        .query_filtered::<(Entity, &Transform), With<Pawn>>()
+        .iter(world)
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
+    let plant_count = world.query_filtered::<(), With<Plant>>().iter(world).count();
+    let game_world = world.resource::<GameWorld>();
+    let cell_size = game_world.cell_size() as f32;
+    let mut pawns: Vec<_> = pawn_positions
+        .into_iter()
+        .map(|(entity, position)| (entity, game_world.cell_at(position).unwrap()))
+        .collect();
+    let spatial_index = world.resource::<SpatialIndex>();
+    for (pawn, cell) in &pawns {
+        assert!(spatial_index.at(*cell, TileOccupant::Pawn).any(|entity| entity == *pawn));
+    }
+    assert_eq!(spatial_index.nearest(UVec2::ZERO, usize::MAX, TileOccupant::Plant).len(), plant_count);
+
+    // moved a few cells over
+    let (pawn, cell) = pawns.remove(0);
+    world.get_mut::<Transform>(pawn).unwrap().translation.x += 3.0 * cell_size;
+    app.update();
+    let moved = cell + UVec2::X * 3;
+    assert_eq!(app.world().resource::<SpatialIndex>().cell_of(pawn), Some(moved));
+    assert_eq!(app.world().resource::<SpatialIndex>().within_radius(moved, 0.0, TileOccupant::Pawn), [(pawn, moved)]);
+
+    app.world_mut().despawn(pawn);
+    app.update();
+    assert_eq!(app.world().resource::<SpatialIndex>().cell_of(pawn), None);
+}
+
+#[test]
+fn trees_slow_down_paths_through_them_until_they_are_gone() {
+    let mut app = headless_app(60, 40);
+    run_until_state(&mut app, GameState::Main);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let (tree, transform) = world.query_filtered::<(Entity, &Transform), With<Plant>>().iter(world).next().unwrap();
+    let cell = world.resource::<GameWorld>().cell_at(transform.translation.truncate()).unwrap();
+    let game_map = world.resource::<GameMap>();
+    let biome_modifier = game_map.biome_at(cell.x, cell.y).movement_modifier;
+    assert_eq!(game_map.obstruction(cell.x, cell.y), 0.6);
+    assert_eq!(game_map.movement_modifier(cell.x, cell.y), biome_modifier * 0.6);
+    let (from, to) = (cell, cell + UVec2::X);
+    let slowed = world.resource::<PathFinder>().find_path(from, to).unwrap();
+    assert_eq!(Some(slowed.clone()), PathFinder::new(game_map).find_path(from, to));
+
+    world.despawn(tree);
+    app.update();
+    let world = app.world();
+    let game_map = world.resource::<GameMap>();
+    assert_eq!(game_map.movement_modifier(cell.x, cell.y), biome_modifier);
+    assert_eq!(world.resource::<PathFinder>().find_path(from, to), PathFinder::new(game_map).find_path(from, to));
+    assert!(world.resource::<PathFinder>().find_path(from, to).unwrap().cost < slowed.cost);
+}
+
+#[test]
+fn new_game_settings_pick_the_map_size_and_the_villagers() {
+    let mut app = headless_app(20, 25);
+    app.world_mut().resource_mut::<NewGameSettings>().pawn_count = 5;
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let game_world = app.world().resource::<GameWorld>();
+    assert_eq!((game_world.width(), game_world.height()), (20, 25));
+    let world = app.world_mut();
+    assert_eq!(world.query_filtered::<(), With<Pawn>>().iter(world).count(), 5);
+}
+
+#[test]
+fn the_accepted_preview_becomes_the_map() {
+    let mut app = headless_menu_app("assets");
+    run_until_state(&mut app, GameState::MainMenu);
+    app.insert_resource(NewGameSettings { width: 30, height: 20, seed: 1, ..default() });
+    set_state(&mut app, GameState::NewGame);
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while app.world().resource::<MapPreview>().map.is_none() {
+        assert!(Instant::now() < deadline, "timed out waiting for the preview");
+        app.update();
+    }
+
+    // rerolling replaces the preview
+    app.world_mut().resource_mut::<NewGameSettings>().seed = 2;
+    app.update();
+    assert!(app.world().resource::<MapPreview>().generation.is_some());
+    while app.world().resource::<MapPreview>().generation.is_some() {
+        assert!(Instant::now() < deadline, "timed out waiting for the preview");
+        app.update();
+    }
+    let (parameters, cells) = app.world().resource::<MapPreview>().map.clone().unwrap();
+    assert_eq!(parameters.seed, 2);
+
+    set_state(&mut app, GameState::MapGeneration);
+    app.update();
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::MapGeneration);
+    app.update();
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Main);
+
+    let game_map = app.world().resource::<GameMap>();
+    for y in 0..20 {
+        for x in 0..30 {
+            assert_eq!(game_map.biome_id_at(x, y), cells[(y * 30 + x) as usize].biome_id);
+        }
+    }
+}
+
+#[test]
+fn malformed_assets_are_reported_instead_of_hanging() {
+    let mut app = headless_app_with_assets(40, 30, "tests/assets/broken");
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while app
+        .world()
+        .get_resource::<LoadingAssets>()
+        .is_none_or(|loading_assets| loading_assets.failed().next().is_none())
+    {
+        assert!(Instant::now() < deadline, "timed out waiting for the asset failure");
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let loading_assets = app.world().resource::<LoadingAssets>();
+    let failed: Vec<_> = loading_assets.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].0.ends_with("broken.biomes.json"));
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Loading);
+}
+
+#[test]
+fn edited_biomes_apply_to_the_running_map() {
+    let mut app = headless_app(40, 30);
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    // edit whichever biome the corner cell ended up with, as if its file had been saved
+    let biome_name = app.world().resource::<GameMap>().biome_at(0, 0).name.clone();
+    let other_tile_name = if biome_name == "Mountain" { "Water" } else { "Mountain" };
+    let mut biomes = app.world_mut().resource_mut::<Assets<Biomes>>();
+    let id = biomes.ids().next().unwrap();
+    let biome = biomes
+        .get_mut(id)
+        .unwrap()
+        .biomes
+        .iter_mut()
+        .find(|biome| biome.name == biome_name)
+        .unwrap();
+    biome.movement_modifier = 0.25;
+    biome.tile_name = Some(other_tile_name.to_string());
+    // one update to send the asset event, one for the game to react to it
+    app.update();
+    app.update();
+
+    let game_map = app.world().resource::<GameMap>();
+    assert_eq!(game_map.movement_modifier(0, 0), 0.25);
+
+    let expected_textures: Vec<_> = {
+        let game_world = app.world().resource::<GameWorld>();
+        game_world
+            .texture_handles_for_biome(game_map.biome_at(0, 0))
+            .into_iter()
+            .map(|(_, index)| index)
+            .collect()
+    };
+    let world = app.world_mut();
+    let corner_texture = world
+        .query::<(&TilePos, &TilemapId, &TileTextureIndex)>()
+        .iter(world)
+        .find(|(tile_pos, tilemap_id, _)| {
+            **tile_pos == TilePos { x: 0, y: 0 } && world.get::<MapChunk>(tilemap_id.0).is_some()
+        })
+        .map(|(_, _, texture_index)| texture_index.0)
+        .unwrap();
+    assert!(expected_textures.contains(&corner_texture));
+}
+
+#[test]
+fn mods_override_and_extend_the_base_definitions() {
+    let mut app = headless_app_with_assets(40, 30, "tests/assets/modded");
+    run_until_state(&mut app, GameState::Main);
+    app.update();
+
+    let active_mods = app.world().resource::<ActiveMods>();
+    let ids: Vec<_> = active_mods.mods.iter().map(|active_mod| active_mod.manifest.id.as_str()).collect();
+    assert_eq!(ids, ["wetlands"]);
+    assert_eq!(active_mods.disabled.len(), 1);
+    assert!(active_mods.disabled[0].0.ends_with("orphan"));
+
+    let game_map = app.world().resource::<GameMap>();
+    let biomes: Vec<_> = game_map
+        .biomes()
+        .iter()
+        .map(|biome| (biome.name.as_str(), biome.movement_modifier))
+        .collect();
+    assert_eq!(biomes, [("Water", 0.5), ("Grass", 1.0), ("Marsh", 0.5)]);
+
+    // the mod gives the base game's leafy trees a light
+    let world = app.world_mut();
+    assert!(world.query_filtered::<(), With<LightSource>>().iter(world).count() > 0);
+}
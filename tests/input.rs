@@ -0,0 +1,129 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use meadowland::resources::input_actions::*;
+use meadowland::resources::settings::Settings;
+use meadowland::systems::input::update_action_state;
+use meadowland::systems::settings::OptionsScreen;
+
+fn update(action_state: &mut ActionState, input_map: &InputMap, keys: &mut ButtonInput<KeyCode>) {
+    action_state.update(input_map, keys, &ButtonInput::default());
+    // what bevy does at the start of every frame
+    keys.clear();
+}
+
+#[test]
+fn actions_follow_their_bindings() {
+    let input_map = InputMap::default();
+    let mut action_state = ActionState::default();
+    let mut keys = ButtonInput::default();
+
+    keys.press(KeyCode::ArrowUp);
+    update(&mut action_state, &input_map, &mut keys);
+    assert!(action_state.just_pressed(InputAction::PanCameraUp));
+    assert!(action_state.pressed(InputAction::PanCameraUp));
+
+    update(&mut action_state, &input_map, &mut keys);
+    assert!(!action_state.just_pressed(InputAction::PanCameraUp));
+    assert!(action_state.pressed(InputAction::PanCameraUp));
+
+    keys.release(KeyCode::ArrowUp);
+    update(&mut action_state, &input_map, &mut keys);
+    assert!(!action_state.pressed(InputAction::PanCameraUp));
+    assert!(action_state.just_released(InputAction::PanCameraUp));
+}
+
+#[test]
+fn modifiers_have_to_match_exactly() {
+    let mut input_map = InputMap::default();
+    let ctrl = Modifiers { ctrl: true, ..default() };
+    input_map.set_bindings(InputAction::ZoomIn, vec![InputBinding::key(KeyCode::KeyW).with_modifiers(ctrl)]);
+    let mut action_state = ActionState::default();
+    let mut keys = ButtonInput::default();
+
+    keys.press(KeyCode::ControlLeft);
+    keys.press(KeyCode::KeyW);
+    update(&mut action_state, &input_map, &mut keys);
+    assert!(action_state.pressed(InputAction::ZoomIn));
+    assert!(!action_state.pressed(InputAction::PanCameraUp));
+
+    keys.release(KeyCode::ControlLeft);
+    update(&mut action_state, &input_map, &mut keys);
+    assert!(!action_state.pressed(InputAction::ZoomIn));
+    assert!(action_state.pressed(InputAction::PanCameraUp));
+}
+
+#[test]
+fn rebinding_takes_the_binding_from_other_actions() {
+    let mut input_map = InputMap::default();
+
+    let unbound = input_map.bind(InputAction::ToggleOptions, 0, InputBinding::key(KeyCode::KeyW));
+    assert_eq!(unbound, [InputAction::PanCameraUp]);
+    assert_eq!(input_map.bindings(InputAction::ToggleOptions), [InputBinding::key(KeyCode::KeyW)]);
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [InputBinding::key(KeyCode::ArrowUp)]);
+    assert!(input_map.conflicts().is_empty());
+}
+
+#[test]
+fn rebinding_keeps_the_alternate_bindings() {
+    let mut input_map = InputMap::default();
+    let (key_w, arrow_up) = (InputBinding::key(KeyCode::KeyW), InputBinding::key(KeyCode::ArrowUp));
+    let (key_i, key_k) = (InputBinding::key(KeyCode::KeyI), InputBinding::key(KeyCode::KeyK));
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [key_w, arrow_up]);
+
+    input_map.bind(InputAction::PanCameraUp, 0, key_i);
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [key_i, arrow_up]);
+    // a slot past the last binding adds one
+    input_map.bind(InputAction::PanCameraUp, 5, key_k);
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [key_i, arrow_up, key_k]);
+    // a binding the action already has moves to the slot instead of showing up twice
+    input_map.bind(InputAction::PanCameraUp, 0, arrow_up);
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [arrow_up, key_k]);
+
+    let unbound = input_map.set_bindings(InputAction::PanCameraUp, vec![key_w, InputBinding::key(KeyCode::KeyS)]);
+    assert_eq!(unbound, [InputAction::PanCameraDown]);
+    assert_eq!(input_map.bindings(InputAction::PanCameraUp), [key_w, InputBinding::key(KeyCode::KeyS)]);
+    assert_eq!(input_map.bindings(InputAction::PanCameraDown), [InputBinding::key(KeyCode::ArrowDown)]);
+}
+
+#[test]
+fn nothing_happens_while_a_binding_is_picked() {
+    let mut world = World::new();
+    world.insert_resource(Settings::default());
+    world.init_resource::<ActionState>();
+    world.init_resource::<OptionsScreen>();
+    world.init_resource::<ButtonInput<MouseButton>>();
+    let mut keys = ButtonInput::default();
+    keys.press(KeyCode::ArrowUp);
+    world.insert_resource(keys);
+
+    world.run_system_once(update_action_state);
+    assert!(world.resource::<ActionState>().pressed(InputAction::PanCameraUp));
+
+    // the held action stops, and the key pressed to bind doesn't start its own
+    world.resource_mut::<OptionsScreen>().start_rebinding(InputAction::ZoomIn, 0);
+    world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyP);
+    world.run_system_once(update_action_state);
+    let action_state = world.resource::<ActionState>();
+    assert!(action_state.just_released(InputAction::PanCameraUp));
+    assert!(InputAction::ALL.iter().all(|action| !action_state.pressed(*action)));
+}
+
+#[test]
+fn conflicts_in_saved_bindings_are_found() {
+    let input_map: InputMap = serde_json::from_str(
+        r#"{
+            "zoomIn": [{ "key": "KeyZ" }],
+            "zoomOut": [{ "key": "KeyZ" }, { "mouse": "Right", "shift": true }]
+        }"#,
+    )
+    .unwrap();
+
+    let conflicts = input_map.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].0, InputBinding::key(KeyCode::KeyZ));
+    assert_eq!(conflicts[0].1, [InputAction::ZoomIn, InputAction::ZoomOut]);
+
+    let shift = Modifiers { shift: true, ..default() };
+    assert_eq!(input_map.bindings(InputAction::ZoomOut)[1], InputBinding::mouse(MouseButton::Right).with_modifiers(shift));
+    assert_eq!(input_map.bindings(InputAction::ZoomOut)[1].to_string(), "Shift+Mouse Right");
+}
@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use meadowland::components::assigned_job::AssignedJob;
+use meadowland::components::mine_designation::MineDesignation;
+use meadowland::components::tile_occupant::TileOccupant;
+use meadowland::components::walk_path::WalkPath;
+use meadowland::components::work_priorities::WorkPriorities;
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::Biomes;
+use meadowland::resources::game_map::GameMap;
+use meadowland::resources::jobs::{JobEvent, JobId, JobState, JobType, Jobs, WorkType};
+use meadowland::resources::path_finder::PathFinder;
+use meadowland::resources::spatial_index::SpatialIndex;
+use meadowland::systems::job_assignment::{assign_jobs, check_jobs};
+use meadowland::systems::mining::clear_abandoned_designations;
+
+/// A 12x6 map of grass, split in two by a column of rock at x 6.
+fn split_map() -> GameMap {
+    let biomes = serde_json::from_str::<Biomes>(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1 },
+            { "name": "Rock", "movementModifier": 0, "kind": "rock", "minHeight": 0.8 }
+        ] }"#,
+    )
+    .unwrap()
+    .biomes;
+    let cell_biomes = (0..6).flat_map(|_| (0..12).map(|x| BiomeId(u16::from(x == 6)))).collect();
+    GameMap::new(12, 6, 1, biomes, cell_biomes)
+}
+
+fn split_world() -> World {
+    let mut world = World::new();
+    world.insert_resource(PathFinder::new(&split_map()));
+    world.init_resource::<SpatialIndex>();
+    world.init_resource::<Jobs>();
+    world.init_resource::<Time>();
+    world
+}
+
+fn spawn_pawn(world: &mut World, cell: UVec2, priorities: WorkPriorities) -> Entity {
+    let pawn = world.spawn(priorities).id();
+    world.resource_mut::<SpatialIndex>().insert(pawn, cell, TileOccupant::Pawn);
+    pawn
+}
+
+fn spawn_plant(world: &mut World, cell: UVec2) -> Entity {
+    let plant = world.spawn_empty().id();
+    world.resource_mut::<SpatialIndex>().insert(plant, cell, TileOccupant::Plant);
+    plant
+}
+
+fn assigned(world: &World, pawn: Entity) -> Option<JobId> {
+    world.get::<AssignedJob>(pawn).map(|AssignedJob(id)| *id)
+}
+
+fn state(world: &World, id: JobId) -> Option<JobState> {
+    world.resource::<Jobs>().get(id).map(|job| job.state().clone())
+}
+
+#[test]
+fn work_priorities_step_around_and_stay_in_range() {
+    let mut priorities = WorkPriorities::default();
+    assert!(WorkType::ALL.iter().all(|work_type| priorities.get(*work_type) == Some(3)));
+
+    priorities.set(WorkType::Haul, Some(9));
+    assert_eq!(priorities.get(WorkType::Haul), Some(WorkPriorities::LOWEST));
+    priorities.set(WorkType::Sow, None);
+    assert_eq!(priorities.get(WorkType::Sow), None);
+
+    let stepped: Vec<_> = std::iter::successors(Some(Some(1)), |priority| Some(WorkPriorities::next(*priority))).take(6).collect();
+    assert_eq!(stepped, [Some(1), Some(2), Some(3), Some(4), None, Some(1)]);
+}
+
+#[test]
+fn jobs_go_through_their_states_and_say_so() {
+    let (pawn, other) = (Entity::from_raw(1), Entity::from_raw(2));
+    let mut jobs = Jobs::default();
+    let mine = jobs.post("Mine", JobType::Mine(UVec2::new(3, 4)));
+    let harvest = jobs.post("Plant Harvest", JobType::PlantHarvest(Entity::from_raw(9)));
+    assert_ne!(mine, harvest);
+    assert_eq!(jobs.find(&JobType::Mine(UVec2::new(3, 4))).map(|job| job.id()), Some(mine));
+
+    jobs.reserve(mine, pawn).unwrap();
+    assert!(jobs.reserve(mine, other).is_err());
+    assert!(jobs.start(mine, other).is_err());
+    jobs.start(mine, pawn).unwrap();
+    assert_eq!(jobs.get(mine).unwrap().holder(), Some(pawn));
+    assert!(jobs.release(mine, other).is_err());
+    jobs.release(mine, pawn).unwrap();
+    assert_eq!(*jobs.get(mine).unwrap().state(), JobState::Open);
+    jobs.finish(mine).unwrap();
+    assert!(jobs.get(mine).is_none());
+    assert!(jobs.finish(mine).is_err());
+    jobs.cancel(harvest).unwrap();
+
+    // ids aren't handed out again
+    let next = jobs.post("Mine", JobType::Mine(UVec2::new(3, 4)));
+    assert!(next != mine && next != harvest);
+
+    let events: Vec<_> = jobs.drain_events().collect();
+    assert_eq!(
+        events,
+        [
+            JobEvent::Posted(mine),
+            JobEvent::Posted(harvest),
+            JobEvent::Reserved(mine, pawn),
+            JobEvent::Started(mine, pawn),
+            JobEvent::Released(mine, pawn),
+            JobEvent::Finished(mine),
+            JobEvent::Cancelled(harvest),
+            JobEvent::Posted(next),
+        ]
+    );
+    assert_eq!(jobs.drain_events().count(), 0);
+}
+
+#[test]
+fn failed_jobs_are_retried_later_and_later_until_given_up() {
+    let mut jobs = Jobs::default();
+    let id = jobs.post("Mine", JobType::Mine(UVec2::ZERO));
+    jobs.drain_events().for_each(drop);
+
+    let mut now = 0.0;
+    let mut waits = vec![];
+    for _ in 0..Jobs::MAX_FAILURES {
+        jobs.fail(id, "It can't be reached", now).unwrap();
+        let Some(JobEvent::Failed { retry_in, .. }) = jobs.drain_events().next() else {
+            panic!("no failure sent");
+        };
+        waits.push(retry_in);
+        if jobs.get(id).is_none() {
+            break;
+        }
+        assert!(jobs.fail(id, "Again", now).is_err());
+
+        // not open until the wait is over
+        jobs.reopen_due(now + retry_in - 0.1);
+        assert!(matches!(jobs.get(id).unwrap().state(), JobState::Failed { .. }));
+        now += retry_in;
+        jobs.reopen_due(now);
+        assert_eq!(*jobs.get(id).unwrap().state(), JobState::Open);
+        assert_eq!(jobs.drain_events().collect::<Vec<_>>(), [JobEvent::Reopened(id)]);
+    }
+
+    assert_eq!(waits, [5.0, 10.0, 20.0, 40.0, 80.0, 120.0]);
+    assert!(jobs.get(id).is_none());
+    assert_eq!(jobs.iter().count(), 0);
+}
+
+#[test]
+fn jobs_go_to_the_nearest_pawn_that_does_the_work() {
+    let mut world = split_world();
+    let plant = spawn_plant(&mut world, UVec2::new(2, 1));
+    let mut jobs = world.resource_mut::<Jobs>();
+    let harvest = jobs.post("Plant Harvest", JobType::PlantHarvest(plant));
+    let far_mine = jobs.post("Mine", JobType::Mine(UVec2::new(10, 3)));
+    let mine = jobs.post("Mine", JobType::Mine(UVec2::new(6, 2)));
+    let low_mine = jobs.post("Mine", JobType::Mine(UVec2::new(6, 4)));
+
+    // the villager is right next to the plant, but no pawn harvests yet
+    let mut villager_priorities = WorkPriorities::default();
+    villager_priorities.set(WorkType::Mine, None);
+    villager_priorities.set(WorkType::Harvest, Some(1));
+    let miner = spawn_pawn(&mut world, UVec2::new(2, 2), WorkPriorities::default());
+    let villager = spawn_pawn(&mut world, UVec2::new(1, 1), villager_priorities);
+
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, miner), Some(mine));
+    assert_eq!(assigned(&world, villager), None);
+    assert_eq!(state(&world, harvest), Some(JobState::Open));
+    // the rock on the other side of the wall can't be walked up to
+    assert_eq!(state(&world, far_mine), Some(JobState::Open));
+
+    // a pawn that stops mining lets go of the rock, one that starts takes the nearest
+    world.get_mut::<WorkPriorities>(miner).unwrap().set(WorkType::Mine, None);
+    world.get_mut::<WorkPriorities>(villager).unwrap().set(WorkType::Mine, Some(2));
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, miner), None);
+    assert_eq!(assigned(&world, villager), Some(mine));
+    assert_eq!(state(&world, low_mine), Some(JobState::Open));
+
+    // once the mining is done, the villager moves on to the next rock
+    world.resource_mut::<Jobs>().finish(mine).unwrap();
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, villager), Some(low_mine));
+    assert_eq!(state(&world, harvest), Some(JobState::Open));
+}
+
+#[test]
+fn jobs_fail_while_out_of_reach() {
+    let mut world = split_world();
+    let mine = world.resource_mut::<Jobs>().post("Mine", JobType::Mine(UVec2::new(3, 4)));
+    let pawn = spawn_pawn(&mut world, UVec2::new(4, 4), WorkPriorities::default());
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, pawn), Some(mine));
+    world.entity_mut(pawn).insert(WalkPath(vec![UVec2::new(3, 3)]));
+
+    // the pawn ends up behind the wall
+    world.resource_mut::<SpatialIndex>().insert(pawn, UVec2::new(9, 2), TileOccupant::Pawn);
+    world.run_system_once(check_jobs);
+    assert!(matches!(state(&world, mine), Some(JobState::Failed { reason, .. }) if reason == "It can't be reached"));
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, pawn), None);
+    assert!(world.get::<WalkPath>(pawn).is_none());
+
+    // the rock opens up for another try once the wait is over
+    world.resource_mut::<SpatialIndex>().insert(pawn, UVec2::new(4, 4), TileOccupant::Pawn);
+    world.resource_mut::<Time>().advance_by(Duration::from_secs_f64(Jobs::RETRY_DELAY));
+    world.run_system_once(check_jobs);
+    assert_eq!(state(&world, mine), Some(JobState::Open));
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, pawn), Some(mine));
+
+    // a pawn that's gone lets go of its job
+    world.despawn(pawn);
+    world.run_system_once(check_jobs);
+    assert_eq!(state(&world, mine), Some(JobState::Open));
+
+    let events: Vec<_> = world.resource_mut::<Jobs>().drain_events().collect();
+    assert!(events.contains(&JobEvent::Released(mine, pawn)));
+    assert_eq!(events.iter().filter(|event| matches!(event, JobEvent::Failed { .. })).count(), 1);
+}
+
+#[test]
+fn rock_given_up_on_loses_its_mark() {
+    let mut world = split_world();
+    let given_up = UVec2::new(6, 1);
+    let kept = UVec2::new(6, 2);
+    let mine = world.resource_mut::<Jobs>().post("Mine", JobType::Mine(given_up));
+    world.resource_mut::<Jobs>().post("Mine", JobType::Mine(kept));
+    let marks = [given_up, kept].map(|cell| world.spawn(MineDesignation { cell, progress: 0.0 }).id());
+
+    for _ in 0..Jobs::MAX_FAILURES {
+        world.resource_mut::<Jobs>().fail(mine, "It can't be reached", 0.0).unwrap();
+        world.resource_mut::<Jobs>().reopen_due(f64::MAX);
+    }
+    assert!(world.resource_mut::<Jobs>().drain_events().any(|event| event == JobEvent::Abandoned(mine)));
+    world.run_system_once(clear_abandoned_designations);
+
+    assert!(world.get_entity(marks[0]).is_none());
+    assert!(world.get_entity(marks[1]).is_some());
+}
+
+#[test]
+fn jobs_whose_target_is_gone_are_cancelled() {
+    let mut world = split_world();
+    let plant = spawn_plant(&mut world, UVec2::new(2, 1));
+    let harvest = world.resource_mut::<Jobs>().post("Plant Harvest", JobType::PlantHarvest(plant));
+    let pawn = spawn_pawn(&mut world, UVec2::new(1, 1), WorkPriorities::default());
+    world.resource_mut::<Jobs>().reserve(harvest, pawn).unwrap();
+    world.resource_mut::<Jobs>().drain_events().for_each(drop);
+
+    // the plant can't come back, so there's nothing to try again
+    world.despawn(plant);
+    world.resource_mut::<SpatialIndex>().remove(plant);
+    world.run_system_once(check_jobs);
+    assert_eq!(state(&world, harvest), None);
+    world.resource_mut::<Time>().advance_by(Duration::from_secs_f64(Jobs::MAX_RETRY_DELAY));
+    world.run_system_once(check_jobs);
+    world.run_system_once(assign_jobs);
+    assert_eq!(assigned(&world, pawn), None);
+
+    let events: Vec<_> = world.resource_mut::<Jobs>().drain_events().collect();
+    assert_eq!(events, [JobEvent::Cancelled(harvest)]);
+}
@@ -0,0 +1,98 @@
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::{Biome, Biomes};
+use meadowland::resources::game_map::GameMap;
+use meadowland::systems::map_generation::{generate_map, MapParameters};
+use meadowland::systems::rivers::RiverParameters;
+
+const GRASS: BiomeId = BiomeId(0);
+const ROCK: BiomeId = BiomeId(1);
+const ORE: BiomeId = BiomeId(2);
+const FLOOR: BiomeId = BiomeId(3);
+
+fn parse(json: &str) -> Biomes {
+    serde_json::from_str(json).unwrap()
+}
+
+fn biomes() -> Vec<Biome> {
+    parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1 },
+            { "name": "Rock", "movementModifier": 0.5, "kind": "rock", "minHeight": 0.5,
+              "mining": { "minedInto": "Floor", "work": 2, "drops": [{ "resource": "Stone", "amount": 2 }] } },
+            { "name": "Ore", "movementModifier": 0, "kind": "rock", "vein": { "host": "Rock", "abundance": 0.1 },
+              "mining": { "minedInto": "Floor", "work": 3 } },
+            { "name": "Floor", "movementModifier": 0.9, "kind": "floor" }
+        ] }"#,
+    )
+    .biomes
+}
+
+#[test]
+fn mining_and_veins_have_to_lead_somewhere() {
+    let biomes = parse(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1,
+              "mining": { "minedInto": "Floor", "work": 1 } },
+            { "name": "Rock", "movementModifier": 0, "kind": "rock", "minHeight": 0.5,
+              "mining": { "minedInto": "Cave", "work": -1, "drops": [{ "resource": "Stone", "amount": 0 }] } },
+            { "name": "Ore", "movementModifier": 0, "kind": "rock", "vein": { "host": "Floor", "abundance": 2 },
+              "mining": { "minedInto": "Rock", "work": 1 } },
+            { "name": "Floor", "movementModifier": 1, "kind": "floor" }
+        ] }"#,
+    );
+
+    let errors = biomes.validate().unwrap_err();
+    assert_eq!(
+        errors,
+        [
+            "Biome Grass can be mined, but only rock can",
+            "Biome Rock is mined into Cave, which isn't a biome",
+            "Biome Rock takes a negative time to mine",
+            "Biome Rock drops no Stone",
+            "Biome Ore is mined into Rock, which is rock too",
+            "Biome Ore runs through Floor, which isn't a biome placed by its thresholds",
+            "Biome Ore has an abundance of 2 outside of [0, 1]",
+        ]
+    );
+}
+
+#[test]
+fn rock_blocks_movement_until_it_is_mined_out() {
+    let mut game_map = GameMap::new(2, 1, 1, biomes(), vec![ROCK, GRASS]);
+    assert!(game_map.is_solid(0, 0));
+    // rock is impassable whatever its movement modifier says
+    assert_eq!(game_map.movement_modifier(0, 0), 0.0);
+    assert_eq!(game_map.mined_into(0, 0), Some(FLOOR));
+    assert_eq!(game_map.mined_into(1, 0), None);
+
+    game_map.set_biome(0, 0, FLOOR);
+    assert!(!game_map.is_solid(0, 0));
+    assert_eq!(game_map.movement_modifier(0, 0), 0.9);
+    assert_eq!(game_map.mined_into(0, 0), None);
+}
+
+#[test]
+fn ore_veins_only_run_through_their_host() {
+    let parameters = MapParameters {
+        width: 200,
+        height: 200,
+        seed: 3,
+        biome_weights: vec![1.0; 4],
+        rivers: RiverParameters::default(),
+    };
+    let cells = generate_map(&biomes(), &parameters);
+
+    // floors only come from mining, and ore only where the cell's thresholds picked the rock
+    assert!(cells.iter().all(|cell| cell.biome_id != FLOOR));
+    let ore = cells.iter().filter(|cell| cell.biome_id == ORE).count();
+    let rock = cells.iter().filter(|cell| cell.biome_id == ROCK).count();
+    assert!(cells.iter().filter(|cell| cell.biome_id == ORE).all(|cell| cell.height >= 0.5));
+    let ore_share = ore as f64 / (ore + rock) as f64;
+    assert!((0.03..0.25).contains(&ore_share), "{ore_share} of the rock is ore");
+
+    // heavier ores get thicker veins
+    let mut parameters = parameters;
+    parameters.biome_weights[ORE.index()] = 3.0;
+    let thicker = generate_map(&biomes(), &parameters).iter().filter(|cell| cell.biome_id == ORE).count();
+    assert!(thicker > ore * 2, "{thicker} ore cells, {ore} before");
+}
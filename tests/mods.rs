@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use meadowland::resources::mods::{ActiveMods, ModManifest};
+
+fn manifest(id: &str, load_order: i32, dependencies: &[&str]) -> (PathBuf, Result<ModManifest, String>) {
+    let manifest = ModManifest {
+        id: id.to_string(),
+        name: None,
+        version: "1.0.0".to_string(),
+        load_order,
+        dependencies: dependencies.iter().map(|dependency| dependency.to_string()).collect(),
+    };
+    (PathBuf::from("mods").join(id), Ok(manifest))
+}
+
+fn active_ids(active_mods: &ActiveMods) -> Vec<&str> {
+    active_mods.mods.iter().map(|active_mod| active_mod.manifest.id.as_str()).collect()
+}
+
+#[test]
+fn mods_load_by_load_order_then_id() {
+    let active_mods = ActiveMods::resolve(vec![manifest("b", 0, &[]), manifest("c", -1, &[]), manifest("a", 0, &[])]);
+    assert_eq!(active_ids(&active_mods), ["c", "a", "b"]);
+    assert!(active_mods.disabled.is_empty());
+}
+
+#[test]
+fn mods_load_after_their_dependencies() {
+    let active_mods = ActiveMods::resolve(vec![manifest("addon", 0, &["big-mod"]), manifest("big-mod", 5, &[])]);
+    assert_eq!(active_ids(&active_mods), ["big-mod", "addon"]);
+}
+
+#[test]
+fn mods_that_cant_be_loaded_are_disabled() {
+    let active_mods = ActiveMods::resolve(vec![
+        manifest("fine", 0, &[]),
+        manifest("orphan", 0, &["missing"]),
+        manifest("needs-orphan", 0, &["orphan"]),
+        manifest("chicken", 0, &["egg"]),
+        manifest("egg", 0, &["chicken"]),
+        (PathBuf::from("mods/fine-copy"), manifest("fine", 0, &[]).1),
+        (PathBuf::from("mods/broken"), Err("mod.json is not valid JSON".to_string())),
+    ]);
+
+    assert_eq!(active_ids(&active_mods), ["fine"]);
+    let reason = |folder: &str| {
+        active_mods
+            .disabled
+            .iter()
+            .find(|(disabled_folder, _)| disabled_folder.ends_with(folder))
+            .map(|(_, reason)| reason.as_str())
+            .unwrap()
+    };
+    assert_eq!(reason("orphan"), "Requires missing, which is not installed");
+    assert_eq!(reason("needs-orphan"), "Requires orphan, which could not be loaded");
+    assert_eq!(reason("chicken"), "Requires egg, which could not be loaded");
+    assert!(reason("fine-copy").contains("already uses the id fine"));
+    assert_eq!(reason("broken"), "mod.json is not valid JSON");
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use meadowland::components::tile_occupant::TileOccupant;
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::{Biome, Biomes};
+use meadowland::resources::game_map::GameMap;
+use meadowland::resources::path_finder::PathFinder;
+use meadowland::resources::spatial_index::SpatialIndex;
+use meadowland::systems::occupancy::{check_placement, nearest_placement};
+
+const GRASS: BiomeId = BiomeId(0);
+const WATER: BiomeId = BiomeId(1);
+const ROCK: BiomeId = BiomeId(2);
+const RIVER: BiomeId = BiomeId(3);
+
+fn biomes() -> Vec<Biome> {
+    serde_json::from_str::<Biomes>(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1 },
+            { "name": "Water", "movementModifier": 0, "kind": "water", "minMoisture": 0.8 },
+            { "name": "Rock", "movementModifier": 0, "kind": "rock", "minHeight": 0.8 },
+            { "name": "River", "movementModifier": 0.5, "kind": "river" }
+        ] }"#,
+    )
+    .unwrap()
+    .biomes
+}
+
+/// A 4x1 strip of grass, water, rock and river.
+fn strip() -> GameMap {
+    GameMap::new(4, 1, 1, biomes(), vec![GRASS, WATER, ROCK, RIVER])
+}
+
+#[test]
+fn plants_and_buildings_need_ground() {
+    let game_map = strip();
+    let spatial_index = SpatialIndex::default();
+    let place = |x, occupant| check_placement(&game_map, &spatial_index, UVec2::new(x, 0), occupant);
+
+    assert_eq!(place(0, TileOccupant::Plant), Ok(()));
+    assert_eq!(place(1, TileOccupant::Plant), Err("Can't place a plant on Water".to_string()));
+    assert_eq!(place(2, TileOccupant::Building), Err("Can't place a building on Rock".to_string()));
+    assert_eq!(place(3, TileOccupant::Plant), Err("Can't place a plant on River".to_string()));
+    // pawns and items only need to be reachable
+    assert_eq!(place(3, TileOccupant::Pawn), Ok(()));
+    assert_eq!(place(1, TileOccupant::Item), Err("Can't place an item on [1, 0], nothing gets there".to_string()));
+    assert_eq!(place(4, TileOccupant::Pawn), Err("Cell [4, 0] is off the map".to_string()));
+}
+
+#[test]
+fn only_some_kinds_share_a_cell() {
+    let game_map = GameMap::new(3, 1, 1, biomes(), vec![GRASS; 3]);
+    let mut spatial_index = SpatialIndex::default();
+    spatial_index.insert(Entity::from_raw(1), UVec2::new(0, 0), TileOccupant::Plant);
+    spatial_index.insert(Entity::from_raw(2), UVec2::new(1, 0), TileOccupant::Building);
+    spatial_index.insert(Entity::from_raw(3), UVec2::new(2, 0), TileOccupant::Item);
+    let place = |x, occupant| check_placement(&game_map, &spatial_index, UVec2::new(x, 0), occupant);
+
+    assert_eq!(place(0, TileOccupant::Plant), Err("Can't place a plant on [0, 0], a plant is there".to_string()));
+    assert_eq!(place(0, TileOccupant::Item), Ok(()));
+    assert_eq!(place(0, TileOccupant::Pawn), Ok(()));
+    assert!(place(1, TileOccupant::Plant).is_err());
+    assert!(place(1, TileOccupant::Item).is_err());
+    assert_eq!(place(1, TileOccupant::Pawn), Ok(()));
+    assert_eq!(place(2, TileOccupant::Item), Ok(()));
+    assert!(place(2, TileOccupant::Building).is_err());
+
+    // the nearest free cell, or none at all
+    assert_eq!(nearest_placement(&game_map, &spatial_index, UVec2::new(0, 0), TileOccupant::Plant), Some(UVec2::new(2, 0)));
+    spatial_index.insert(Entity::from_raw(4), UVec2::new(2, 0), TileOccupant::Plant);
+    assert_eq!(nearest_placement(&game_map, &spatial_index, UVec2::new(0, 0), TileOccupant::Plant), None);
+}
+
+#[test]
+fn obstructions_slow_and_block_paths() {
+    let mut game_map = GameMap::new(5, 3, 1, biomes(), vec![GRASS; 15]);
+    assert!(game_map.set_obstruction(2, 1, 0.5));
+    assert!(!game_map.set_obstruction(2, 1, 0.5));
+    assert_eq!(game_map.movement_modifier(2, 1), 0.5);
+    // the biome changing underneath keeps what's on the cell
+    game_map.set_biome(2, 1, RIVER);
+    assert_eq!(game_map.movement_modifier(2, 1), 0.25);
+
+    // a wall across the middle column with a door in the top cell
+    let mut path_finder = PathFinder::new(&game_map);
+    let walked = path_finder.find_path(UVec2::new(0, 1), UVec2::new(4, 1)).unwrap();
+    assert!(!walked.cells.contains(&UVec2::new(2, 1)), "{:?}", walked.cells);
+    game_map.set_obstruction(2, 0, 0.0);
+    game_map.set_obstruction(2, 1, 0.0);
+    path_finder.update_cells(&game_map, &[UVec2::new(2, 0), UVec2::new(2, 1)]);
+    let detour = path_finder.find_path(UVec2::new(0, 1), UVec2::new(4, 1)).unwrap();
+    assert!(detour.cells.contains(&UVec2::new(2, 2)), "{:?}", detour.cells);
+    game_map.set_obstruction(2, 2, 0.0);
+    path_finder.update_cells(&game_map, &[UVec2::new(2, 2)]);
+    assert!(!path_finder.is_reachable(UVec2::new(0, 1), UVec2::new(4, 1)));
+}
@@ -0,0 +1,212 @@
+use bevy::math::UVec2;
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::{Biome, Biomes};
+use meadowland::resources::game_map::GameMap;
+use meadowland::resources::path_finder::{Path, PathFinder};
+use meadowland::systems::map_generation::{generate_map, MapParameters};
+use meadowland::systems::rivers::RiverParameters;
+
+const GRASS: BiomeId = BiomeId(0);
+const ROCK: BiomeId = BiomeId(1);
+const FLOOR: BiomeId = BiomeId(2);
+const MUD: BiomeId = BiomeId(3);
+
+fn biomes() -> Vec<Biome> {
+    serde_json::from_str::<Biomes>(
+        r#"{ "biomes": [
+            { "name": "Grass", "movementModifier": 1 },
+            { "name": "Rock", "movementModifier": 0, "kind": "rock", "minHeight": 0.6,
+              "mining": { "minedInto": "Floor", "work": 1 } },
+            { "name": "Floor", "movementModifier": 1, "kind": "floor" },
+            { "name": "Mud", "movementModifier": 0.25, "minMoisture": 0.6 }
+        ] }"#,
+    )
+    .unwrap()
+    .biomes
+}
+
+fn generated_map(size: u32, seed: u64) -> GameMap {
+    let parameters = MapParameters {
+        width: size,
+        height: size,
+        seed,
+        biome_weights: vec![1.0; 4],
+        rivers: RiverParameters::default(),
+    };
+    let cells = generate_map(&biomes(), &parameters);
+    GameMap::new(size, size, seed, biomes(), cells.iter().map(|cell| cell.biome_id).collect())
+}
+
+/// A map of grass with the cells drawn as `#` turned to rock, the first row of `rows` is the top one.
+fn drawn_map(rows: &[&str]) -> GameMap {
+    let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+    let cell_biomes = rows
+        .iter()
+        .rev()
+        .flat_map(|row| row.chars().map(|char| if char == '#' { ROCK } else { GRASS }))
+        .collect();
+    GameMap::new(width, height, 1, biomes(), cell_biomes)
+}
+
+fn assert_walkable(game_map: &GameMap, path: &Path, from: UVec2, to: UVec2) {
+    assert_eq!(path.cells.first(), Some(&from));
+    assert_eq!(path.cells.last(), Some(&to));
+    for cell in &path.cells {
+        assert!(game_map.movement_modifier(cell.x, cell.y) > 0.0, "the path crosses {cell}");
+    }
+    for step in path.cells.windows(2) {
+        let delta = step[1].as_ivec2() - step[0].as_ivec2();
+        assert!(delta.abs().max_element() == 1, "{} to {} isn't a step", step[0], step[1]);
+        // diagonal steps don't cut the corners of rock
+        if delta.x != 0 && delta.y != 0 {
+            assert!(game_map.movement_modifier(step[1].x, step[0].y) > 0.0, "{} to {} cuts a corner", step[0], step[1]);
+            assert!(game_map.movement_modifier(step[0].x, step[1].y) > 0.0, "{} to {} cuts a corner", step[0], step[1]);
+        }
+    }
+}
+
+/// Spread out cells of the map that can be walked on, picked the same way every run.
+fn walkable_cells(game_map: &GameMap, count: usize) -> Vec<UVec2> {
+    let mut state = 0x2545_f491_u64;
+    let mut cells = vec![];
+    while cells.len() < count {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let cell = UVec2::new((state % game_map.width() as u64) as u32, ((state >> 32) % game_map.height() as u64) as u32);
+        if game_map.movement_modifier(cell.x, cell.y) > 0.0 {
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+#[test]
+fn hierarchical_paths_are_close_to_the_shortest() {
+    let game_map = generated_map(160, 5);
+    // the paths run over slow mud and around rock, not just grass
+    for biome in [GRASS, ROCK, MUD] {
+        let count = (0..160).flat_map(|y| (0..160).map(move |x| (x, y))).filter(|(x, y)| game_map.biome_id_at(*x, *y) == biome).count();
+        assert!(count > 160, "only {count} cells of {biome:?}");
+    }
+    let path_finder = PathFinder::new(&game_map);
+    let cells = walkable_cells(&game_map, 80);
+
+    let mut found = 0;
+    for pair in cells.chunks(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let flat = path_finder.find_path_flat(from, to);
+        let path = path_finder.find_path(from, to);
+        assert_eq!(path.is_some(), flat.is_some(), "from {from} to {to}");
+        assert_eq!(path_finder.is_reachable(from, to), flat.is_some(), "from {from} to {to}");
+        let (Some(flat), Some(path)) = (flat, path) else {
+            continue;
+        };
+        found += 1;
+        assert_walkable(&game_map, &path, from, to);
+        assert_walkable(&game_map, &flat, from, to);
+        assert!(path.cost >= flat.cost - 1e-3, "from {from} to {to}: {} beats the shortest {}", path.cost, flat.cost);
+        assert!(path.cost <= flat.cost * 1.2 + 2.0, "from {from} to {to}: {} against {}", path.cost, flat.cost);
+    }
+    assert!(found >= 20, "only {found} of the pairs reach each other");
+}
+
+#[test]
+fn walled_off_cells_are_unreachable() {
+    let game_map = drawn_map(&[
+        "......#.....",
+        "......#.....",
+        "......#..###",
+        "......#..#..",
+        "......#..#..",
+        "......#.....",
+    ]);
+    let path_finder = PathFinder::new(&game_map);
+
+    assert!(!path_finder.is_reachable(UVec2::new(0, 0), UVec2::new(11, 0)));
+    assert_eq!(path_finder.find_path(UVec2::new(0, 0), UVec2::new(11, 0)), None);
+    assert_eq!(path_finder.find_path_flat(UVec2::new(0, 0), UVec2::new(11, 0)), None);
+    // rock can't be stood on, but dug at from next to it
+    assert!(!path_finder.is_reachable(UVec2::new(0, 0), UVec2::new(6, 0)));
+    assert!(path_finder.is_reachable_next_to(UVec2::new(0, 0), UVec2::new(6, 0)));
+    assert!(!path_finder.is_reachable_next_to(UVec2::new(0, 0), UVec2::new(11, 0)));
+
+    let path = path_finder.find_path(UVec2::new(11, 0), UVec2::new(11, 5)).unwrap();
+    assert_walkable(&game_map, &path, UVec2::new(11, 0), UVec2::new(11, 5));
+}
+
+#[test]
+fn slow_ground_is_walked_around() {
+    let mut game_map = drawn_map(&[".....", ".....", ".....", ".....", "....."]);
+    for y in 1..5 {
+        game_map.set_biome(2, y, MUD);
+    }
+    let path_finder = PathFinder::new(&game_map);
+
+    let path = path_finder.find_path(UVec2::new(0, 1), UVec2::new(4, 1)).unwrap();
+    assert!(path.cells.contains(&UVec2::new(2, 0)), "{:?}", path.cells);
+    assert_eq!(path, path_finder.find_path_flat(UVec2::new(0, 1), UVec2::new(4, 1)).unwrap());
+}
+
+#[test]
+fn changed_cells_are_taken_into_account() {
+    let mut game_map = generated_map(96, 11);
+    let mut path_finder = PathFinder::new(&game_map);
+    let cells = walkable_cells(&game_map, 40);
+
+    // a wall across the middle of the map with a single gap, and all the rock mined out on one side
+    let mut changed = vec![];
+    for y in 0..game_map.height() {
+        game_map.set_biome(48, y, if y == 70 { GRASS } else { ROCK });
+        changed.push(UVec2::new(48, y));
+        for x in 0..48 {
+            if game_map.is_solid(x, y) {
+                game_map.set_biome(x, y, FLOOR);
+                changed.push(UVec2::new(x, y));
+            }
+        }
+    }
+    path_finder.update_cells(&game_map, &changed);
+    let rebuilt = PathFinder::new(&game_map);
+
+    for pair in cells.chunks(2) {
+        let (from, to) = (pair[0], pair[1]);
+        assert_eq!(path_finder.is_reachable(from, to), rebuilt.is_reachable(from, to), "from {from} to {to}");
+        assert_eq!(path_finder.find_path(from, to), rebuilt.find_path(from, to), "from {from} to {to}");
+        if let Some(path) = path_finder.find_path(from, to) {
+            assert_walkable(&game_map, &path, from, to);
+            if (from.x < 48) != (to.x < 48) {
+                assert!(path.cells.contains(&UVec2::new(48, 70)), "from {from} to {to} skips the gap");
+            }
+        }
+    }
+}
+
+#[test]
+fn changed_cells_leave_the_other_clusters_be() {
+    let mut game_map = drawn_map(&[".".repeat(64).as_str(); 64]);
+    let mut path_finder = PathFinder::new(&game_map);
+    let revisions = |path_finder: &PathFinder| -> Vec<u32> {
+        (0..4).flat_map(|y| (0..4).map(move |x| UVec2::new(x, y) * 16)).map(|cell| path_finder.cluster_revision(cell)).collect()
+    };
+    let before = revisions(&path_finder);
+
+    // rock in the middle of a cluster changes none of its borders
+    game_map.set_biome(24, 24, ROCK);
+    path_finder.update_cells(&game_map, &[UVec2::new(24, 24)]);
+    let after = revisions(&path_finder);
+    for (index, (before, after)) in before.iter().zip(&after).enumerate() {
+        assert_eq!(*after, before + u32::from(index == 5), "cluster {index}");
+    }
+
+    // rock on a border changes the transitions of the clusters on both sides of it, and nothing else
+    game_map.set_biome(31, 40, ROCK);
+    path_finder.update_cells(&game_map, &[UVec2::new(31, 40)]);
+    for (index, (before, later)) in after.iter().zip(revisions(&path_finder)).enumerate() {
+        assert_eq!(later, before + u32::from(index == 9 || index == 10), "cluster {index}");
+    }
+
+    assert!(!path_finder.is_reachable(UVec2::new(0, 0), UVec2::new(31, 40)));
+    let (from, to) = (UVec2::new(30, 40), UVec2::new(32, 40));
+    assert_eq!(path_finder.find_path(from, to), PathFinder::new(&game_map).find_path(from, to));
+}
@@ -0,0 +1,92 @@
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::{Biome, Biomes};
+use meadowland::systems::map_generation::{generate_map, MapGenerationCell, MapParameters};
+use meadowland::systems::rivers::{carve_rivers, RiverParameters};
+
+const LAND: BiomeId = BiomeId(0);
+const WATER: BiomeId = BiomeId(1);
+const RIVER: BiomeId = BiomeId(2);
+
+fn biomes() -> Vec<Biome> {
+    let json = r#"{ "biomes": [
+        { "name": "Grass", "movementModifier": 1 },
+        { "name": "Water", "movementModifier": 0, "kind": "water" },
+        { "name": "River", "movementModifier": 0.4, "kind": "river" }
+    ] }"#;
+    serde_json::from_str::<Biomes>(json).unwrap().biomes
+}
+
+/// A 30x9 slope falling towards the sea on its right, and from its edges towards a valley along the middle row.
+fn slope() -> Vec<MapGenerationCell> {
+    let mut cells = vec![];
+    for y in 0..9 {
+        for x in 0..30 {
+            cells.push(MapGenerationCell {
+                height: 1.0 - x as f64 / 30.0 + (y as f64 - 4.0).abs() * 0.01,
+                biome_id: if x >= 25 { WATER } else { LAND },
+                matched: true,
+                ..Default::default()
+            });
+        }
+    }
+    cells
+}
+
+fn parameters(rivers: RiverParameters) -> MapParameters {
+    MapParameters { width: 30, height: 9, seed: 1, biome_weights: vec![], rivers }
+}
+
+#[test]
+fn rivers_flow_downhill_into_the_water() {
+    let mut cells = slope();
+    let rivers = RiverParameters { count: 1, min_source_height: 0.99, widening_flow: u32::MAX, max_half_width: 2, meander: 0.0 };
+    carve_rivers(&mut cells, &biomes(), &parameters(rivers));
+
+    let river_cells: Vec<_> = (0..cells.len()).filter(|index| cells[*index].biome_id == RIVER).collect();
+    let columns: Vec<_> = river_cells.iter().map(|index| index % 30).collect();
+    // one cell wide, starting high on the slope and running without gaps down the valley to the sea
+    for x in 1..25 {
+        assert_eq!(columns.iter().filter(|column| **column == x).count(), 1, "column {x}: {columns:?}");
+    }
+    assert!(river_cells.iter().filter(|index| **index % 30 > 3).all(|index| index / 30 == 4));
+    assert!(cells.iter().skip(25).step_by(30).all(|cell| cell.biome_id == WATER));
+}
+
+#[test]
+fn rivers_widen_as_they_drain_more_land() {
+    let mut cells = slope();
+    let rivers = RiverParameters { count: 1, min_source_height: 0.99, widening_flow: 40, max_half_width: 1, meander: 0.0 };
+    carve_rivers(&mut cells, &biomes(), &parameters(rivers));
+
+    let width_at = |x: usize| (0..9).filter(|y| cells[y * 30 + x].biome_id == RIVER).count();
+    assert_eq!(width_at(2), 1);
+    assert!(width_at(24) > 1);
+}
+
+#[test]
+fn tables_without_a_river_biome_get_no_rivers() {
+    let mut cells = slope();
+    let mut biomes = biomes();
+    biomes.pop();
+    carve_rivers(&mut cells, &biomes, &parameters(RiverParameters { count: 5, ..Default::default() }));
+
+    assert!(cells.iter().all(|cell| cell.biome_id != RIVER));
+}
+
+#[test]
+fn rivers_are_the_same_for_the_same_seed() {
+    let json = std::fs::read_to_string("assets/biomes/details.biomes.json").unwrap();
+    let biomes = serde_json::from_str::<Biomes>(&json).unwrap().biomes;
+    let river_id = biomes.iter().position(|biome| biome.name == "River").unwrap();
+    let parameters = MapParameters {
+        width: 120,
+        height: 120,
+        seed: 11,
+        biome_weights: vec![1.0; biomes.len()],
+        rivers: RiverParameters { count: 10, ..Default::default() },
+    };
+
+    let cells = generate_map(&biomes, &parameters);
+    assert!(cells.iter().any(|cell| cell.biome_id.index() == river_id));
+    assert_eq!(cells, generate_map(&biomes, &parameters));
+}
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use meadowland::plugins::validated_json_asset_plugin::{generated_schemas, schema_validator, schema_violations};
+use serde_json::{json, Value};
+
+/// The schema each kind of data file is checked against, by file extension.
+const DATA_FILE_SCHEMAS: &[(&str, &str)] = &[
+    (".biomes.json", "biome.json"),
+    (".resource.json", "resource.json"),
+    (".plant.json", "plant.json"),
+];
+
+fn files_under(path: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(path).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files_under(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_shipped_data_file_matches_its_schema() {
+    let mut files = vec![];
+    files_under(Path::new("assets"), &mut files);
+
+    let mut checked = 0;
+    for file in files {
+        let file_name = file.file_name().unwrap().to_str().unwrap();
+        let Some((_, schema_name)) = DATA_FILE_SCHEMAS
+            .iter()
+            .find(|(extension, _)| file_name.ends_with(extension))
+        else {
+            continue;
+        };
+
+        let validator = schema_validator(schema_name).unwrap();
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        let violations = schema_violations(&validator, &value);
+        assert!(violations.is_empty(), "{}: {violations:?}", file.display());
+        checked += 1;
+    }
+
+    assert!(checked > 0);
+}
+
+#[test]
+fn violations_point_at_the_offending_value() {
+    let validator = schema_validator("biome.json").unwrap();
+    let value = json!({
+        "biomes": [
+            { "name": "Grass", "movementModifier": 1, "minHeight": 0, "minMoisture": 0, "minHeat": 0 },
+            { "name": "Peaks", "movementModifier": 1, "minHeight": 1.5, "minMoisture": 0, "minHeat": 0 }
+        ]
+    });
+
+    let violations = schema_violations(&validator, &value);
+    assert_eq!(violations.len(), 1, "{violations:?}");
+    assert!(violations[0].starts_with("/biomes/1/minHeight: "), "{violations:?}");
+}
+
+#[test]
+fn nested_definitions_are_validated() {
+    let validator = schema_validator("plant.json").unwrap();
+
+    let lit = json!({ "name": "glowcap", "light": { "range": 3, "color": [0.2, 0.8, 1.0] } });
+    assert!(schema_violations(&validator, &lit).is_empty());
+
+    let bad_color = json!({ "name": "glowcap", "light": { "range": 3, "color": [0.2, 0.8] } });
+    let violations = schema_violations(&validator, &bad_color);
+    assert!(violations.iter().any(|violation| violation.starts_with("/light/color")), "{violations:?}");
+}
+
+#[test]
+fn checked_in_schemas_match_the_data_types() {
+    for (file_name, generated) in generated_schemas() {
+        let path = Path::new("schemas").join(file_name);
+        let checked_in = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            checked_in == generated,
+            "{} is out of date, regenerate it with `cargo run --bin schemagen`",
+            path.display()
+        );
+    }
+}
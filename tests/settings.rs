@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use bevy::prelude::KeyCode;
+use meadowland::resources::input_actions::{InputAction, InputBinding};
+use meadowland::resources::settings::{Settings, WindowModeSetting};
+
+/// A fresh folder for one test's settings file.
+fn settings_folder(test_name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("meadowland-settings-{}-{test_name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    folder
+}
+
+#[test]
+fn missing_settings_are_written_with_defaults() {
+    let path = settings_folder("missing").join("settings.json");
+
+    let settings = Settings::load_or_regenerate(&path);
+    assert_eq!(settings, Settings::default());
+    assert!(path.exists());
+}
+
+#[test]
+fn corrupt_settings_are_backed_up_and_regenerated() {
+    let folder = settings_folder("corrupt");
+    let path = folder.join("settings.json");
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(&path, "{ \"vsync\": tru").unwrap();
+
+    let settings = Settings::load_or_regenerate(&path);
+    assert_eq!(settings, Settings::default());
+    assert_eq!(std::fs::read_to_string(folder.join("settings.json.bak")).unwrap(), "{ \"vsync\": tru");
+    assert_eq!(Settings::load_or_regenerate(&path), Settings::default());
+}
+
+#[test]
+fn saved_settings_load_back() {
+    let path = settings_folder("saved").join("settings.json");
+
+    let mut settings = Settings {
+        window_mode: WindowModeSetting::Windowed,
+        frame_cap: None,
+        ui_scale: 1.5,
+        ..Settings::default()
+    };
+    settings.keybindings.bind(InputAction::ToggleOptions, 0, InputBinding::key(KeyCode::F10));
+    settings.save_to(&path).unwrap();
+
+    assert_eq!(Settings::load_or_regenerate(&path), settings);
+}
+
+#[test]
+fn partial_settings_fill_in_the_defaults() {
+    let folder = settings_folder("partial");
+    let path = folder.join("settings.json");
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(&path, r#"{ "vsync": false, "uiScale": 10 }"#).unwrap();
+
+    let settings = Settings::load_or_regenerate(&path);
+    assert!(!settings.vsync);
+    assert_eq!(settings.ui_scale, Settings::MAX_UI_SCALE);
+    assert_eq!(settings.resolution, Settings::default().resolution);
+}
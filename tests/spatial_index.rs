@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use meadowland::components::tile_occupant::TileOccupant;
+use meadowland::resources::spatial_index::SpatialIndex;
+
+/// Entities spread over a 100x100 map the same way every run, every fourth one a pawn, the rest plants.
+fn scattered() -> Vec<(Entity, UVec2, TileOccupant)> {
+    let mut state = 0x9e37_79b9_u64;
+    (0..300)
+        .map(|index| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let cell = UVec2::new((state % 100) as u32, ((state >> 32) % 100) as u32);
+            let occupant = if index % 4 == 0 { TileOccupant::Pawn } else { TileOccupant::Plant };
+            (Entity::from_raw(index), cell, occupant)
+        })
+        .collect()
+}
+
+fn indexed(entities: &[(Entity, UVec2, TileOccupant)]) -> SpatialIndex {
+    let mut spatial_index = SpatialIndex::default();
+    for (entity, cell, occupant) in entities {
+        spatial_index.insert(*entity, *cell, *occupant);
+    }
+    spatial_index
+}
+
+fn by_distance(entities: &[(Entity, UVec2, TileOccupant)], center: UVec2, occupant: TileOccupant) -> Vec<(Entity, UVec2)> {
+    let mut found: Vec<_> = entities
+        .iter()
+        .filter(|(_, _, entity_occupant)| *entity_occupant == occupant)
+        .map(|(entity, cell, _)| (*entity, *cell))
+        .collect();
+    found.sort_by_key(|(entity, cell)| (cell.as_ivec2().distance_squared(center.as_ivec2()), *entity));
+    found
+}
+
+#[test]
+fn queries_find_what_going_over_every_entity_finds() {
+    let entities = scattered();
+    let spatial_index = indexed(&entities);
+
+    for center in [UVec2::new(0, 0), UVec2::new(50, 50), UVec2::new(99, 3), UVec2::new(17, 82)] {
+        for occupant in [TileOccupant::Pawn, TileOccupant::Plant] {
+            let expected = by_distance(&entities, center, occupant);
+            for k in [1, 5, 40] {
+                assert_eq!(spatial_index.nearest(center, k, occupant), expected[..k], "{k} nearest to {center}");
+            }
+            assert_eq!(spatial_index.nearest(center, 1000, occupant), expected);
+
+            let within: Vec<_> = expected
+                .iter()
+                .copied()
+                .filter(|(_, cell)| cell.as_vec2().distance(center.as_vec2()) <= 12.5)
+                .collect();
+            assert_eq!(spatial_index.within_radius(center, 12.5, occupant), within, "within 12.5 of {center}");
+        }
+    }
+    assert_eq!(spatial_index.nearest(UVec2::new(5, 5), 3, TileOccupant::Building), []);
+}
+
+#[test]
+fn entities_move_between_cells_and_leave() {
+    let (tree, pawn, stack) = (Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3));
+    let mut spatial_index = SpatialIndex::default();
+    spatial_index.insert(tree, UVec2::new(4, 4), TileOccupant::Plant);
+    spatial_index.insert(pawn, UVec2::new(4, 4), TileOccupant::Pawn);
+    spatial_index.insert(stack, UVec2::new(30, 2), TileOccupant::Item);
+
+    assert_eq!(spatial_index.at(UVec2::new(4, 4), TileOccupant::Plant).collect::<Vec<_>>(), [tree]);
+    assert_eq!(spatial_index.all_at(UVec2::new(4, 4)).count(), 2);
+
+    // across a bucket's edge
+    spatial_index.insert(pawn, UVec2::new(29, 2), TileOccupant::Pawn);
+    assert_eq!(spatial_index.cell_of(pawn), Some(UVec2::new(29, 2)));
+    assert_eq!(spatial_index.at(UVec2::new(4, 4), TileOccupant::Pawn).count(), 0);
+    assert_eq!(spatial_index.nearest(UVec2::new(0, 0), 1, TileOccupant::Pawn), [(pawn, UVec2::new(29, 2))]);
+    assert_eq!(spatial_index.within_radius(UVec2::new(29, 2), 1.0, TileOccupant::Item), [(stack, UVec2::new(30, 2))]);
+
+    spatial_index.remove(stack);
+    assert_eq!(spatial_index.cell_of(stack), None);
+    assert_eq!(spatial_index.nearest(UVec2::new(29, 2), 5, TileOccupant::Item), []);
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use meadowland::resources::biome_registry::BiomeId;
+use meadowland::resources::biomes::{Biome, Biomes};
+use meadowland::resources::game_map::GameMap;
+use meadowland::systems::transitions::{blend_edges, transitions_at, EdgeMask, Transition};
+
+const WATER: BiomeId = BiomeId(0);
+const GRASS: BiomeId = BiomeId(1);
+const MOUNTAIN: BiomeId = BiomeId(2);
+const FOREST: BiomeId = BiomeId(3);
+const SAND: BiomeId = BiomeId(4);
+
+fn biomes() -> Vec<Biome> {
+    let json = r#"{ "biomes": [
+        { "name": "Water", "movementModifier": 0, "transitions": { "layer": 0 } },
+        { "name": "Grass", "movementModifier": 1, "transitions": { "layer": 1 } },
+        { "name": "Mountain", "movementModifier": 0.3, "transitions": { "layer": 2, "reach": 0.25 } },
+        { "name": "Forest", "movementModifier": 0.9, "tileName": "Grass", "transitions": { "layer": 2 } },
+        { "name": "Sand", "movementModifier": 1 }
+    ] }"#;
+    serde_json::from_str::<Biomes>(json).unwrap().biomes
+}
+
+/// A 3x3 map, `rows` given from the top row down like it's drawn.
+fn map(rows: [[BiomeId; 3]; 3]) -> GameMap {
+    let cell_biomes = rows.iter().rev().flatten().copied().collect();
+    GameMap::new(3, 3, 1, biomes(), cell_biomes)
+}
+
+#[test]
+fn higher_layers_blend_over_their_neighbors() {
+    let game_map = map([
+        [WATER, GRASS, GRASS],
+        [WATER, WATER, WATER],
+        [WATER, WATER, MOUNTAIN],
+    ]);
+
+    // grass along the north side covers the north east corner, the mountain only has its corner
+    assert_eq!(
+        transitions_at(&game_map, 1, 1),
+        [
+            Transition { biome_id: GRASS, mask: EdgeMask::NORTH },
+            Transition { biome_id: MOUNTAIN, mask: EdgeMask::SOUTH_EAST },
+        ]
+    );
+    assert_eq!(
+        transitions_at(&game_map, 2, 1),
+        [
+            Transition { biome_id: GRASS, mask: EdgeMask::NORTH },
+            Transition { biome_id: MOUNTAIN, mask: EdgeMask::SOUTH },
+        ]
+    );
+    // lower layers never blend over higher ones
+    assert_eq!(transitions_at(&game_map, 1, 2), []);
+    assert_eq!(transitions_at(&game_map, 2, 0), []);
+}
+
+#[test]
+fn biomes_sharing_tiles_or_without_transitions_meet_with_hard_edges() {
+    let game_map = map([
+        [GRASS, FOREST, SAND],
+        [GRASS, GRASS, SAND],
+        [GRASS, GRASS, GRASS],
+    ]);
+
+    // forest draws on grass's tiles, so it never blends over grass
+    assert_eq!(transitions_at(&game_map, 0, 1), []);
+    // sand has no transitions, so the others blend over it but it blends over nothing
+    assert_eq!(
+        transitions_at(&game_map, 2, 1),
+        [
+            Transition { biome_id: GRASS, mask: EdgeMask::WEST | EdgeMask::SOUTH },
+            Transition { biome_id: FOREST, mask: EdgeMask::NORTH_WEST },
+        ]
+    );
+    assert_eq!(
+        transitions_at(&game_map, 2, 2),
+        [
+            Transition { biome_id: GRASS, mask: EdgeMask::SOUTH_WEST },
+            Transition { biome_id: FOREST, mask: EdgeMask::WEST },
+        ]
+    );
+    assert_eq!(transitions_at(&game_map, 1, 1), []);
+}
+
+#[test]
+fn corners_next_to_a_covered_side_are_dropped() {
+    assert_eq!((EdgeMask::NORTH | EdgeMask::NORTH_EAST | EdgeMask::NORTH_WEST).reduced(), EdgeMask::NORTH);
+    assert_eq!((EdgeMask::EAST | EdgeMask::SOUTH_WEST).reduced(), EdgeMask::EAST | EdgeMask::SOUTH_WEST);
+    assert_eq!(EdgeMask::SOUTH_EAST.reduced(), EdgeMask::SOUTH_EAST);
+}
+
+#[test]
+fn blended_edges_fade_away_from_their_side() {
+    let texture = Image::new_fill(
+        Extent3d { width: 8, height: 8, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[10, 20, 30, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let blended = blend_edges(&texture, EdgeMask::NORTH, 0.5).unwrap();
+
+    let alpha = |x: u32, y: u32| blended.data[((y * 8 + x) * 4 + 3) as usize];
+    // the top row of the image is the north side
+    assert!(alpha(3, 0) > 200);
+    assert!(alpha(3, 2) < alpha(3, 1));
+    assert_eq!(alpha(3, 4), 0);
+    assert_eq!(alpha(3, 7), 0);
+    assert_eq!(&blended.data[..3], [10, 20, 30]);
+
+    let float_texture = Image::new_fill(
+        Extent3d { width: 8, height: 8, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0; 16],
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::default(),
+    );
+    assert!(blend_edges(&float_texture, EdgeMask::NORTH, 0.5).is_none());
+}